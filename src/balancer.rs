@@ -0,0 +1,419 @@
+use std::{
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use base64::Engine as _;
+use color_eyre::eyre::{OptionExt as _, WrapErr as _, eyre};
+use tokio::{
+    io::{AsyncBufReadExt as _, AsyncReadExt as _, AsyncWriteExt as _},
+    net::{TcpListener, TcpStream},
+};
+
+use crate::{
+    connector::AsyncReadWrite,
+    proxy::{Proxy, ProxyType},
+    utils::pretty_error,
+};
+
+/// Consecutive handshake failures after which a proxy is skipped by
+/// [`Balancer::connect_with_retry`] for [`DEMOTION_COOLDOWN`], mirroring the
+/// per-host circuit breaker in `http.rs`.
+const CONSECUTIVE_FAILURES_TO_DEMOTE: usize = 3;
+
+/// How long a demoted proxy is skipped before it's eligible again. A
+/// successful dial clears the demotion immediately, so this only bounds
+/// how long a proxy that's still down stays excluded.
+const DEMOTION_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Rotates incoming client connections across the checked working proxies,
+/// so pointing a browser at `listen_addr` gets automatic rotation instead
+/// of picking one proxy out of the exported list by hand.
+pub struct Balancer {
+    proxies: Vec<Proxy>,
+    cursor: AtomicUsize,
+    consecutive_failures: Vec<AtomicUsize>,
+    demoted_until: Vec<parking_lot::Mutex<Option<Instant>>>,
+    max_retries: usize,
+}
+
+impl Balancer {
+    pub fn new(proxies: Vec<Proxy>, max_retries: usize) -> Self {
+        let consecutive_failures =
+            proxies.iter().map(|_| AtomicUsize::new(0)).collect();
+        let demoted_until =
+            proxies.iter().map(|_| parking_lot::Mutex::new(None)).collect();
+        Self {
+            proxies,
+            cursor: AtomicUsize::new(0),
+            consecutive_failures,
+            demoted_until,
+            max_retries,
+        }
+    }
+
+    fn next_index(&self) -> Option<usize> {
+        if self.proxies.is_empty() {
+            return None;
+        }
+        Some(self.cursor.fetch_add(1, Ordering::Relaxed) % self.proxies.len())
+    }
+
+    /// `true` while `index`'s cooldown hasn't elapsed yet. An expired
+    /// cooldown clears itself here, so the proxy is retried on its next
+    /// turn instead of staying excluded forever.
+    fn is_demoted(&self, index: usize) -> bool {
+        let mut demoted_until = self.demoted_until[index].lock();
+        match *demoted_until {
+            Some(until) if Instant::now() < until => true,
+            Some(_) => {
+                *demoted_until = None;
+                false
+            }
+            None => false,
+        }
+    }
+
+    async fn connect_with_retry(
+        &self,
+        dst_host: &str,
+        dst_port: u16,
+    ) -> crate::Result<Box<dyn AsyncReadWrite>> {
+        let attempts = self.max_retries.min(self.proxies.len()).max(1);
+        let mut last_err = None;
+        for _ in 0..attempts {
+            let Some(index) = self.next_index() else {
+                return Err(eyre!("no working proxies available"));
+            };
+            if self.is_demoted(index) {
+                continue;
+            }
+            let proxy = &self.proxies[index];
+            match dial_upstream(proxy, dst_host, dst_port).await {
+                Ok(stream) => {
+                    self.consecutive_failures[index]
+                        .store(0, Ordering::Relaxed);
+                    *self.demoted_until[index].lock() = None;
+                    return Ok(stream);
+                }
+                Err(e) => {
+                    let failures = self.consecutive_failures[index]
+                        .fetch_add(1, Ordering::Relaxed)
+                        + 1;
+                    if failures >= CONSECUTIVE_FAILURES_TO_DEMOTE {
+                        *self.demoted_until[index].lock() =
+                            Some(Instant::now() + DEMOTION_COOLDOWN);
+                    }
+                    if tracing::event_enabled!(tracing::Level::DEBUG) {
+                        tracing::debug!(
+                            "balancer: {} | {}",
+                            proxy.to_string(true),
+                            pretty_error(&e)
+                        );
+                    }
+                    last_err = Some(e);
+                }
+            }
+        }
+        Err(last_err.unwrap_or_else(|| eyre!("no working proxies available")))
+    }
+
+    pub async fn run(
+        self,
+        listen_addr: SocketAddr,
+        token: tokio_util::sync::CancellationToken,
+    ) -> crate::Result<()> {
+        let balancer = std::sync::Arc::new(self);
+        let listener =
+            TcpListener::bind(listen_addr).await.wrap_err_with(|| {
+                format!("failed to bind balancer listener on {listen_addr}")
+            })?;
+        tracing::info!("Load balancer listening on {listen_addr}");
+
+        loop {
+            tokio::select! {
+                biased;
+                () = token.cancelled() => break,
+                accepted = listener.accept() => {
+                    let (stream, _) = accepted
+                        .wrap_err("failed to accept balancer connection")?;
+                    let balancer = std::sync::Arc::clone(&balancer);
+                    tokio::spawn(async move {
+                        if let Err(e) = balancer.handle_connection(stream).await
+                            && tracing::event_enabled!(tracing::Level::DEBUG)
+                        {
+                            tracing::debug!(
+                                "balancer connection failed: {}",
+                                pretty_error(&e)
+                            );
+                        }
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_connection(&self, mut stream: TcpStream) -> crate::Result<()> {
+        let mut probe = [0_u8];
+        let peeked = stream.peek(&mut probe).await?;
+        if peeked == 1 && probe[0] == 0x05 {
+            self.handle_socks5(stream).await
+        } else {
+            self.handle_http(stream).await
+        }
+    }
+
+    async fn handle_socks5(&self, mut stream: TcpStream) -> crate::Result<()> {
+        let mut greeting = [0_u8; 2];
+        stream.read_exact(&mut greeting).await?;
+        let nmethods = greeting[1];
+        let mut methods = vec![0_u8; nmethods as usize];
+        stream.read_exact(&mut methods).await?;
+        // No authentication required.
+        stream.write_all(&[0x05, 0x00]).await?;
+
+        let (dst_host, dst_port) = read_socks5_request(&mut stream).await?;
+        match self.connect_with_retry(&dst_host, dst_port).await {
+            Ok(mut upstream) => {
+                write_socks5_reply(&mut stream, true).await?;
+                tokio::io::copy_bidirectional(&mut stream, &mut upstream)
+                    .await?;
+                Ok(())
+            }
+            Err(e) => {
+                write_socks5_reply(&mut stream, false).await?;
+                Err(e)
+            }
+        }
+    }
+
+    async fn handle_http(&self, stream: TcpStream) -> crate::Result<()> {
+        let mut client = tokio::io::BufReader::new(stream);
+
+        let mut request_line = String::new();
+        client.read_line(&mut request_line).await?;
+        let mut parts = request_line.split_whitespace();
+        let method = parts
+            .next()
+            .ok_or_eyre("empty HTTP request line")?
+            .to_owned();
+        let target =
+            parts.next().ok_or_eyre("missing request target")?.to_owned();
+
+        let mut headers = String::new();
+        loop {
+            let mut header_line = String::new();
+            client.read_line(&mut header_line).await?;
+            if header_line.is_empty() || header_line == "\r\n" {
+                break;
+            }
+            headers.push_str(&header_line);
+        }
+
+        let (dst_host, dst_port) =
+            parse_http_target(&method, &target, &headers)?;
+
+        let mut upstream =
+            self.connect_with_retry(&dst_host, dst_port).await?;
+
+        if method.eq_ignore_ascii_case("CONNECT") {
+            client
+                .get_mut()
+                .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+                .await?;
+        } else {
+            upstream
+                .write_all(format!("{method} {target} HTTP/1.1\r\n").as_bytes())
+                .await?;
+            upstream.write_all(headers.as_bytes()).await?;
+            upstream.write_all(b"\r\n").await?;
+        }
+
+        tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+        Ok(())
+    }
+}
+
+/// Dials `proxy` and tunnels to `dst_host:dst_port` through it, returning a
+/// stream that behaves as if connected directly to the destination. Shared
+/// with [`crate::proxy_protocol::probe`], which needs the exact same
+/// HTTP-CONNECT/SOCKS5 dialing this balancer already does.
+pub(crate) async fn dial_upstream(
+    proxy: &Proxy,
+    dst_host: &str,
+    dst_port: u16,
+) -> crate::Result<Box<dyn AsyncReadWrite>> {
+    match proxy.protocol {
+        ProxyType::Http => Ok(Box::new(
+            connect_via_http_connect(proxy, dst_host, dst_port).await?,
+        )),
+        ProxyType::Socks5 => {
+            let upstream_addr = format!("{}:{}", proxy.host, proxy.port);
+            let target: tokio_socks::TargetAddr<'_> =
+                tokio_socks::TargetAddr::Domain(dst_host.into(), dst_port);
+            let stream = if let (Some(username), Some(password)) =
+                (proxy.username.as_deref(), proxy.password.as_deref())
+            {
+                tokio_socks::tcp::Socks5Stream::connect_with_password(
+                    upstream_addr.as_str(),
+                    target,
+                    username,
+                    password,
+                )
+                .await
+            } else {
+                tokio_socks::tcp::Socks5Stream::connect(
+                    upstream_addr.as_str(),
+                    target,
+                )
+                .await
+            }
+            .map_err(|e| {
+                eyre!("failed to connect via {}: {e}", proxy.to_string(true))
+            })?;
+            Ok(Box::new(stream))
+        }
+        ProxyType::Socks4 => Err(eyre!(
+            "{} is a socks4 proxy, which the load balancer does not support",
+            proxy.to_string(true)
+        )),
+    }
+}
+
+async fn connect_via_http_connect(
+    proxy: &Proxy,
+    dst_host: &str,
+    dst_port: u16,
+) -> crate::Result<TcpStream> {
+    let mut stream = TcpStream::connect((proxy.host.as_str(), proxy.port))
+        .await
+        .map_err(|e| {
+            eyre!("failed to connect to {}: {e}", proxy.to_string(true))
+        })?;
+
+    let mut request =
+        format!("CONNECT {dst_host}:{dst_port} HTTP/1.1\r\nHost: {dst_host}:{dst_port}\r\n");
+    if let (Some(username), Some(password)) =
+        (proxy.username.as_deref(), proxy.password.as_deref())
+    {
+        let credentials = base64::engine::general_purpose::STANDARD
+            .encode(format!("{username}:{password}"));
+        request.push_str(&format!("Proxy-Authorization: Basic {credentials}\r\n"));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut reader = tokio::io::BufReader::new(&mut stream);
+    let mut status_line = String::new();
+    reader.read_line(&mut status_line).await?;
+    if !status_line.contains(" 200 ") {
+        return Err(eyre!(
+            "upstream {} rejected CONNECT: {}",
+            proxy.to_string(true),
+            status_line.trim()
+        ));
+    }
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).await?;
+        if line.is_empty() || line == "\r\n" {
+            break;
+        }
+    }
+    drop(reader);
+    Ok(stream)
+}
+
+async fn read_socks5_request(
+    stream: &mut TcpStream,
+) -> crate::Result<(String, u16)> {
+    let mut header = [0_u8; 4];
+    stream.read_exact(&mut header).await?;
+    let [version, command, _reserved, address_type] = header;
+    if version != 0x05 {
+        return Err(eyre!("unsupported SOCKS version {version}"));
+    }
+    if command != 0x01 {
+        return Err(eyre!("only the SOCKS5 CONNECT command is supported"));
+    }
+
+    let dst_host = match address_type {
+        0x01 => {
+            let mut buf = [0_u8; 4];
+            stream.read_exact(&mut buf).await?;
+            Ipv4Addr::from(buf).to_string()
+        }
+        0x03 => {
+            let len = stream.read_u8().await?;
+            let mut buf = vec![0_u8; len as usize];
+            stream.read_exact(&mut buf).await?;
+            String::from_utf8(buf).map_err(|e| {
+                eyre!("invalid domain in SOCKS5 request: {e}")
+            })?
+        }
+        0x04 => {
+            let mut buf = [0_u8; 16];
+            stream.read_exact(&mut buf).await?;
+            Ipv6Addr::from(buf).to_string()
+        }
+        _ => return Err(eyre!("unsupported SOCKS5 address type {address_type}")),
+    };
+    let dst_port = stream.read_u16().await?;
+    Ok((dst_host, dst_port))
+}
+
+async fn write_socks5_reply(
+    stream: &mut TcpStream,
+    success: bool,
+) -> crate::Result<()> {
+    let reply_code = if success { 0x00 } else { 0x01 };
+    stream
+        .write_all(&[
+            0x05, reply_code, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ])
+        .await?;
+    Ok(())
+}
+
+/// Extracts the destination host/port a local HTTP client wants, either from
+/// a `CONNECT host:port` request line or from an absolute-form request URI
+/// (port defaulting to the scheme's), falling back to the `Host` header for
+/// any other request target (port 80 when the header names none).
+fn parse_http_target(
+    method: &str,
+    target: &str,
+    headers: &str,
+) -> crate::Result<(String, u16)> {
+    if method.eq_ignore_ascii_case("CONNECT") {
+        let (host, port) =
+            target.rsplit_once(':').ok_or_eyre("invalid CONNECT target")?;
+        let port: u16 =
+            port.parse().wrap_err("invalid port in CONNECT target")?;
+        return Ok((host.to_owned(), port));
+    }
+
+    if let Ok(url) = url::Url::parse(target) {
+        let host = url.host_str().ok_or_eyre("request URI has no host")?;
+        let port = url.port_or_known_default().ok_or_eyre(
+            "request URI has no port and no default for its scheme",
+        )?;
+        return Ok((host.to_owned(), port));
+    }
+
+    let host_header = headers
+        .lines()
+        .find_map(|line| line.strip_prefix("Host:").or_else(|| line.strip_prefix("host:")))
+        .ok_or_eyre("relative request URI with no Host header")?
+        .trim();
+    host_header.rsplit_once(':').map_or_else(
+        || Ok((host_header.to_owned(), 80)),
+        |(host, port)| {
+            Ok((
+                host.to_owned(),
+                port.parse().wrap_err("invalid port in Host header")?,
+            ))
+        },
+    )
+}
@@ -4,7 +4,27 @@ use color_eyre::eyre::OptionExt as _;
 
 #[cfg(feature = "tui")]
 use crate::event::{AppEvent, Event};
-use crate::{config::Config, proxy::Proxy, utils::pretty_error};
+use crate::{
+    config::{Config, HttpbinResponse},
+    parsers::parse_ipv4,
+    proxy::Proxy,
+    utils::pretty_error,
+};
+
+async fn fetch_real_ip(config: &Config) -> Option<compact_str::CompactString> {
+    let check_url = config.checking.check_url.clone()?;
+    let client = reqwest::ClientBuilder::new()
+        .user_agent(config.checking.user_agent.as_bytes())
+        .timeout(config.checking.timeout)
+        .connect_timeout(config.checking.connect_timeout)
+        .build()
+        .ok()?;
+    let text = client.get(check_url).send().await.ok()?.text().await.ok()?;
+    serde_json::from_str::<HttpbinResponse>(&text)
+        .ok()
+        .and_then(|httpbin| parse_ipv4(&httpbin.origin))
+        .or_else(|| parse_ipv4(&text))
+}
 
 pub async fn check_all<R: reqwest::dns::Resolve + 'static>(
     config: Arc<Config>,
@@ -12,11 +32,20 @@ pub async fn check_all<R: reqwest::dns::Resolve + 'static>(
     proxies: Vec<Proxy>,
     token: tokio_util::sync::CancellationToken,
     #[cfg(feature = "tui")] tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    #[cfg(feature = "tui")] pause: crate::pause::PauseControl,
+    #[cfg(feature = "tui")] asn_db: Option<
+        Arc<maxminddb::Reader<maxminddb::Mmap>>,
+    >,
+    #[cfg(feature = "tui")] geo_db: Option<
+        Arc<maxminddb::Reader<maxminddb::Mmap>>,
+    >,
 ) -> crate::Result<Vec<Proxy>> {
     if config.checking.check_url.is_none() {
         return Ok(proxies);
     }
 
+    let real_ip = Arc::new(fetch_real_ip(&config).await);
+
     let workers_count =
         config.checking.max_concurrent_checks.min(proxies.len());
     if workers_count == 0 {
@@ -25,6 +54,7 @@ pub async fn check_all<R: reqwest::dns::Resolve + 'static>(
 
     #[cfg(not(feature = "tui"))]
     tracing::info!("Started checking {} proxies", proxies.len());
+    crate::systemd::notify_status(&format!("checking {} proxies", proxies.len()));
 
     let queue = Arc::new(parking_lot::Mutex::new(proxies));
     let checked_proxies = Arc::new(parking_lot::Mutex::new(Vec::new()));
@@ -36,24 +66,72 @@ pub async fn check_all<R: reqwest::dns::Resolve + 'static>(
         let dns_resolver = Arc::clone(&dns_resolver);
         let checked_proxies = Arc::clone(&checked_proxies);
         let token = token.clone();
+        let real_ip = Arc::clone(&real_ip);
         #[cfg(feature = "tui")]
         let tx = tx.clone();
+        #[cfg(feature = "tui")]
+        let pause = pause.clone();
+        #[cfg(feature = "tui")]
+        let asn_db = asn_db.clone();
+        #[cfg(feature = "tui")]
+        let geo_db = geo_db.clone();
         join_set.spawn(async move {
             tokio::select! {
                 biased;
                 res = async move {
                     loop {
+                        #[cfg(feature = "tui")]
+                        pause.wait_if_paused().await;
+
                         let Some(mut proxy) = queue.lock().pop() else {
                             break;
                         };
-                        let check_result = proxy.check(&config, Arc::clone(&dns_resolver)).await;
+                        let check_result = proxy
+                            .check(&config, Arc::clone(&dns_resolver), real_ip.as_deref())
+                            .await;
                         #[cfg(feature = "tui")]
                         drop(tx.send(Event::App(AppEvent::ProxyChecked(proxy.protocol))));
                         match check_result {
                             Ok(()) => {
                                 #[cfg(feature = "tui")]
-                                drop(tx.send(Event::App(AppEvent::ProxyWorking(proxy.protocol))));
-                                checked_proxies.lock().push(proxy);
+                                {
+                                    drop(tx.send(Event::App(AppEvent::ProxyWorking(proxy.protocol))));
+                                    drop(tx.send(Event::App(AppEvent::ProxyIdentity {
+                                        protocol: proxy.protocol,
+                                        exit_ip_resolved: proxy.exit_ip.is_some(),
+                                        anonymity: proxy.anonymity,
+                                        proxy_protocol_supported: proxy.proxy_protocol_supported,
+                                    })));
+                                    let geo = crate::ipdb::resolve_geo(
+                                        proxy.exit_ip.as_deref(),
+                                        asn_db.as_deref(),
+                                        geo_db.as_deref(),
+                                    )
+                                    .unwrap_or_default();
+                                    drop(tx.send(Event::App(AppEvent::ProxyWorkingDetails {
+                                        protocol: proxy.protocol,
+                                        addr: compact_str::format_compact!(
+                                            "{}:{}",
+                                            proxy.host,
+                                            proxy.port
+                                        ),
+                                        latency_secs: proxy
+                                            .timeout
+                                            .unwrap_or_default()
+                                            .as_secs_f64(),
+                                        country: geo
+                                            .country
+                                            .map(compact_str::CompactString::from),
+                                        asn: geo.asn,
+                                        anonymity: proxy.anonymity,
+                                    })));
+                                }
+                                if !config.checking.elite_only
+                                    || proxy.anonymity
+                                        == Some(crate::proxy::Anonymity::Elite)
+                                {
+                                    checked_proxies.lock().push(proxy);
+                                }
                             }
                             Err(e) if tracing::event_enabled!(tracing::Level::DEBUG) => {
                                 tracing::debug!(
@@ -75,7 +153,13 @@ pub async fn check_all<R: reqwest::dns::Resolve + 'static>(
     drop(config);
     drop(dns_resolver);
     drop(token);
+    drop(real_ip);
     drop(tx);
+    drop(pause);
+    #[cfg(feature = "tui")]
+    drop(asn_db);
+    #[cfg(feature = "tui")]
+    drop(geo_db);
 
     while let Some(res) = join_set.join_next().await {
         match res {
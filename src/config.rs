@@ -1,14 +1,20 @@
 use std::{
     collections::hash_map,
+    net::IpAddr,
+    num::NonZero,
     path::{Path, PathBuf},
     sync::Arc,
     time::Duration,
 };
 
-use color_eyre::eyre::{OptionExt as _, WrapErr as _};
+use color_eyre::eyre::{OptionExt as _, WrapErr as _, eyre};
 
 use crate::{
-    HashMap, http::BasicAuth, proxy::ProxyType, raw_config, utils::is_docker,
+    HashMap, connector::ProxyConnector, discovery::DiscoveryProvider,
+    http::{BasicAuth, SourceAuth},
+    output::Compression, proxy::ProxyType, raw_config,
+    tls::TlsConfig,
+    utils::is_docker,
 };
 
 pub const APP_DIRECTORY_NAME: &str = "proxy_scraper_checker";
@@ -16,20 +22,41 @@ pub const APP_DIRECTORY_NAME: &str = "proxy_scraper_checker";
 #[derive(serde::Deserialize)]
 pub struct HttpbinResponse {
     pub origin: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
 }
 
 pub struct Source {
     pub url: String,
-    pub basic_auth: Option<BasicAuth>,
+    pub auth: Option<SourceAuth>,
     pub headers: Option<HashMap<String, String>>,
+    pub custom_scheme: Option<compact_str::CompactString>,
+}
+
+pub struct CircuitBreakerConfig {
+    pub failure_threshold: NonZero<usize>,
+    pub cooldown: Duration,
+}
+
+pub struct RetryConfig {
+    pub max_retries: usize,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter_fraction: f64,
+    pub extra_retryable_statuses: Vec<reqwest::StatusCode>,
+    pub circuit_breaker: CircuitBreakerConfig,
 }
 
 pub struct ScrapingConfig {
     pub max_proxies_per_source: usize,
+    pub max_source_bytes: u64,
+    pub max_cidr_hosts: u64,
     pub timeout: Duration,
     pub connect_timeout: Duration,
     pub proxy: Option<url::Url>,
+    pub proxy_auth: Option<BasicAuth>,
     pub user_agent: String,
+    pub retry: RetryConfig,
     pub sources: HashMap<ProxyType, Vec<Arc<Source>>>,
 }
 
@@ -39,16 +66,32 @@ pub struct CheckingConfig {
     pub timeout: Duration,
     pub connect_timeout: Duration,
     pub user_agent: String,
+    pub elite_only: bool,
+    pub probe_proxy_protocol: bool,
+    pub spki_pins: Vec<compact_str::CompactString>,
 }
 
 pub struct TxtOutputConfig {
     pub enabled: bool,
+    pub compression: Compression,
+    pub group_by_country: bool,
+    pub group_by_asn: bool,
 }
 
 pub struct JsonOutputConfig {
     pub enabled: bool,
     pub include_asn: bool,
     pub include_geolocation: bool,
+    pub include_anonymity: bool,
+    pub compression: Compression,
+}
+
+#[derive(Default)]
+pub struct GeoFilterConfig {
+    pub allowed_countries: Vec<String>,
+    pub blocked_countries: Vec<String>,
+    pub allowed_asns: Vec<u32>,
+    pub blocked_asns: Vec<u32>,
 }
 
 pub struct OutputConfig {
@@ -56,6 +99,58 @@ pub struct OutputConfig {
     pub sort_by_speed: bool,
     pub txt: TxtOutputConfig,
     pub json: JsonOutputConfig,
+    pub geo_filter: GeoFilterConfig,
+}
+
+pub struct IntervalConfig {
+    pub rerun_every: Duration,
+}
+
+pub struct DohConfig {
+    pub endpoint: url::Url,
+    pub cache_size: NonZero<usize>,
+    pub overrides: HashMap<compact_str::CompactString, Vec<IpAddr>>,
+}
+
+#[derive(Default)]
+pub struct DnsConfig {
+    pub doh: Option<DohConfig>,
+}
+
+pub struct UpstreamProxyConfig {
+    pub url: url::Url,
+    pub remote_dns: bool,
+}
+
+pub struct BalancerConfig {
+    pub listen_addr: std::net::SocketAddr,
+    pub max_retries: NonZero<usize>,
+}
+
+pub struct StatusServerConfig {
+    pub listen_addr: std::net::SocketAddr,
+}
+
+#[derive(Clone)]
+pub enum GeoIpSource {
+    Maxmind { account_id: String, license_key: String },
+    Url(String),
+    Path(PathBuf),
+}
+
+#[derive(Default)]
+pub struct IpdbConfig {
+    pub asn_source: Option<GeoIpSource>,
+    pub geo_source: Option<GeoIpSource>,
+}
+
+pub struct ServeConfig {
+    pub listen_addr: std::net::SocketAddr,
+}
+
+#[derive(Default)]
+pub struct DiscoveryConfig {
+    pub providers: Vec<Arc<dyn DiscoveryProvider>>,
 }
 
 pub struct Config {
@@ -63,6 +158,16 @@ pub struct Config {
     pub scraping: ScrapingConfig,
     pub checking: CheckingConfig,
     pub output: OutputConfig,
+    pub interval: IntervalConfig,
+    pub dns: DnsConfig,
+    pub tls: TlsConfig,
+    pub discovery: DiscoveryConfig,
+    pub ipdb: IpdbConfig,
+    pub upstream_proxy: Option<Arc<UpstreamProxyConfig>>,
+    pub balancer: Option<BalancerConfig>,
+    pub status_server: Option<StatusServerConfig>,
+    pub serve: Option<ServeConfig>,
+    pub custom_connectors: HashMap<compact_str::CompactString, Arc<dyn ProxyConnector>>,
 }
 
 async fn get_output_path(
@@ -83,13 +188,47 @@ async fn get_output_path(
     Ok(output_path)
 }
 
+async fn probe_reachable(
+    url: &url::Url,
+    timeout: Duration,
+) -> crate::Result<()> {
+    let host = url.host_str().ok_or_eyre("url has no host")?;
+    let port =
+        url.port_or_known_default().ok_or_eyre("url has no known port")?;
+    tokio::time::timeout(
+        timeout,
+        tokio::net::TcpStream::connect((host, port)),
+    )
+    .await
+    .map_err(|_| eyre!("connection timed out"))??;
+    Ok(())
+}
+
+fn convert_geoip_source(
+    source: raw_config::GeoIpSourceConfig,
+) -> GeoIpSource {
+    match source {
+        raw_config::GeoIpSourceConfig::Maxmind { account_id, license_key } => {
+            GeoIpSource::Maxmind { account_id, license_key }
+        }
+        raw_config::GeoIpSourceConfig::Url { url } => GeoIpSource::Url(url),
+        raw_config::GeoIpSourceConfig::Path { path } => GeoIpSource::Path(path),
+    }
+}
+
 impl Config {
-    pub const fn asn_enabled(&self) -> bool {
-        self.output.json.enabled && self.output.json.include_asn
+    pub fn asn_enabled(&self) -> bool {
+        (self.output.json.enabled && self.output.json.include_asn)
+            || (self.output.txt.enabled && self.output.txt.group_by_asn)
+            || !self.output.geo_filter.allowed_asns.is_empty()
+            || !self.output.geo_filter.blocked_asns.is_empty()
     }
 
-    pub const fn geolocation_enabled(&self) -> bool {
-        self.output.json.enabled && self.output.json.include_geolocation
+    pub fn geolocation_enabled(&self) -> bool {
+        (self.output.json.enabled && self.output.json.include_geolocation)
+            || (self.output.txt.enabled && self.output.txt.group_by_country)
+            || !self.output.geo_filter.allowed_countries.is_empty()
+            || !self.output.geo_filter.blocked_countries.is_empty()
     }
 
     pub fn enabled_protocols(
@@ -102,6 +241,61 @@ impl Config {
         self.scraping.sources.contains_key(&protocol)
     }
 
+    pub const fn watch_enabled(&self) -> bool {
+        !self.interval.rerun_every.is_zero()
+    }
+
+    /// Fails fast, before any workers are spawned, if the configured
+    /// concurrency can't actually be satisfied or the check/scraping
+    /// upstreams are unreachable.
+    pub async fn preflight(&self) -> crate::Result<()> {
+        self.preflight_fd_headroom()?;
+        self.preflight_reachability().await
+    }
+
+    fn preflight_fd_headroom(&self) -> crate::Result<()> {
+        let requested = self.checking.max_concurrent_checks;
+        let mut reserved = Vec::with_capacity(requested);
+        for opened in 0..requested {
+            let socket = socket2::Socket::new(
+                socket2::Domain::IPV4,
+                socket2::Type::STREAM,
+                None,
+            )
+            .map_err(|e| {
+                eyre!(
+                    "failed to reserve socket {}/{requested} needed for \
+                     max_concurrent_checks: {e}. Lower max_concurrent_checks \
+                     or raise your OS file descriptor limit.",
+                    opened + 1
+                )
+            })?;
+            reserved.push(socket);
+        }
+        drop(reserved);
+        Ok(())
+    }
+
+    async fn preflight_reachability(&self) -> crate::Result<()> {
+        if let Some(check_url) = &self.checking.check_url {
+            probe_reachable(check_url, self.checking.connect_timeout)
+                .await
+                .wrap_err_with(|| {
+                    format!("check_url ({check_url}) is not reachable")
+                })?;
+        }
+
+        if let Some(proxy_url) = &self.scraping.proxy {
+            probe_reachable(proxy_url, self.scraping.connect_timeout)
+                .await
+                .wrap_err_with(|| {
+                    format!("scraping.proxy ({proxy_url}) is not reachable")
+                })?;
+        }
+
+        Ok(())
+    }
+
     pub async fn from_raw_config(
         raw_config: raw_config::RawConfig,
     ) -> crate::Result<Self> {
@@ -130,12 +324,44 @@ impl Config {
                 max_proxies_per_source: raw_config
                     .scraping
                     .max_proxies_per_source,
+                max_source_bytes: raw_config.scraping.max_source_bytes,
+                max_cidr_hosts: raw_config.scraping.max_cidr_hosts,
                 timeout: Duration::from_secs_f64(raw_config.scraping.timeout),
                 connect_timeout: Duration::from_secs_f64(
                     raw_config.scraping.connect_timeout,
                 ),
                 proxy: raw_config.scraping.proxy,
+                proxy_auth: raw_config.scraping.proxy_auth,
                 user_agent: raw_config.scraping.user_agent,
+                retry: RetryConfig {
+                    max_retries: raw_config.scraping.retry.max_retries,
+                    initial_delay: Duration::from_secs_f64(
+                        raw_config.scraping.retry.initial_delay,
+                    ),
+                    max_delay: Duration::from_secs_f64(
+                        raw_config.scraping.retry.max_delay,
+                    ),
+                    jitter_fraction: raw_config.scraping.retry.jitter_fraction,
+                    extra_retryable_statuses: raw_config
+                        .scraping
+                        .retry
+                        .extra_retryable_statuses
+                        .into_iter()
+                        .filter_map(|code| {
+                            reqwest::StatusCode::from_u16(code).ok()
+                        })
+                        .collect(),
+                    circuit_breaker: CircuitBreakerConfig {
+                        failure_threshold: raw_config
+                            .scraping
+                            .retry
+                            .circuit_breaker
+                            .failure_threshold,
+                        cooldown: Duration::from_secs_f64(
+                            raw_config.scraping.retry.circuit_breaker.cooldown,
+                        ),
+                    },
+                },
                 sources: [
                     (ProxyType::Http, raw_config.scraping.http),
                     (ProxyType::Socks4, raw_config.scraping.socks4),
@@ -165,11 +391,24 @@ impl Config {
                     raw_config.checking.connect_timeout,
                 ),
                 user_agent: raw_config.checking.user_agent,
+                elite_only: raw_config.checking.elite_only,
+                probe_proxy_protocol: raw_config.checking.probe_proxy_protocol,
+                spki_pins: raw_config
+                    .checking
+                    .spki_pins
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
             },
             output: OutputConfig {
                 path: output_path,
                 sort_by_speed: raw_config.output.sort_by_speed,
-                txt: TxtOutputConfig { enabled: raw_config.output.txt.enabled },
+                txt: TxtOutputConfig {
+                    enabled: raw_config.output.txt.enabled,
+                    compression: raw_config.output.txt.compression,
+                    group_by_country: raw_config.output.txt.group_by_country,
+                    group_by_asn: raw_config.output.txt.group_by_asn,
+                },
                 json: JsonOutputConfig {
                     enabled: raw_config.output.json.enabled,
                     include_asn: raw_config.output.json.include_asn,
@@ -177,22 +416,112 @@ impl Config {
                         .output
                         .json
                         .include_geolocation,
+                    include_anonymity: raw_config
+                        .output
+                        .json
+                        .include_anonymity,
+                    compression: raw_config.output.json.compression,
+                },
+                geo_filter: GeoFilterConfig {
+                    allowed_countries: raw_config
+                        .output
+                        .geo_filter
+                        .allowed_countries,
+                    blocked_countries: raw_config
+                        .output
+                        .geo_filter
+                        .blocked_countries,
+                    allowed_asns: raw_config.output.geo_filter.allowed_asns,
+                    blocked_asns: raw_config.output.geo_filter.blocked_asns,
                 },
             },
+            interval: IntervalConfig {
+                rerun_every: Duration::from_secs_f64(
+                    raw_config.interval.rerun_every,
+                ),
+            },
+            dns: DnsConfig {
+                doh: raw_config.dns.doh.map(|doh| DohConfig {
+                    endpoint: doh.endpoint,
+                    cache_size: doh.cache_size,
+                    overrides: doh
+                        .overrides
+                        .into_iter()
+                        .map(|(host, addrs)| (host.into(), addrs))
+                        .collect(),
+                }),
+            },
+            tls: raw_config.tls,
+            discovery: DiscoveryConfig {
+                providers: raw_config
+                    .discovery
+                    .backends
+                    .into_iter()
+                    .map(|backend| {
+                        let raw_config::DiscoveryBackendConfig::Shodan {
+                            api_key,
+                            search_query,
+                            rate_limit,
+                            max_results,
+                        } = backend;
+                        Arc::new(crate::discovery::ShodanProvider {
+                            api_key,
+                            search_query,
+                            rate_limit: Duration::from_secs_f64(rate_limit),
+                            max_results,
+                        }) as Arc<dyn DiscoveryProvider>
+                    })
+                    .collect(),
+            },
+            ipdb: IpdbConfig {
+                asn_source: raw_config.ipdb.asn_source.map(convert_geoip_source),
+                geo_source: raw_config.ipdb.geo_source.map(convert_geoip_source),
+            },
+            upstream_proxy: raw_config.upstream_proxy.map(|url| {
+                let remote_dns = url.scheme() == "socks5h";
+                Arc::new(UpstreamProxyConfig { url, remote_dns })
+            }),
+            balancer: raw_config.balancer.map(|balancer| BalancerConfig {
+                listen_addr: balancer.listen_addr,
+                max_retries: balancer.max_retries,
+            }),
+            status_server: raw_config.status_server.map(|status_server| {
+                StatusServerConfig { listen_addr: status_server.listen_addr }
+            }),
+            serve: raw_config.serve.map(|serve| ServeConfig {
+                listen_addr: serve.listen_addr,
+            }),
+            custom_connectors: HashMap::default(),
         })
     }
+
+    /// Registers a [`ProxyConnector`] for a custom scheme, so sources tagged
+    /// with that scheme (via `custom_scheme` in their source config) are
+    /// checked through it instead of the built-in HTTP/SOCKS paths. Meant
+    /// for embedding this crate to test exotic proxy setups without
+    /// forking it; the bundled binary never calls this itself.
+    #[must_use]
+    pub fn register_connector(
+        mut self,
+        scheme: impl Into<compact_str::CompactString>,
+        connector: Arc<dyn ProxyConnector>,
+    ) -> Self {
+        self.custom_connectors.insert(scheme.into(), connector);
+        self
+    }
 }
 
 impl From<raw_config::SourceConfig> for Source {
     fn from(sc: raw_config::SourceConfig) -> Self {
         match sc {
             raw_config::SourceConfig::Simple(url) => {
-                Self { url, basic_auth: None, headers: None }
+                Self { url, auth: None, headers: None, custom_scheme: None }
             }
             raw_config::SourceConfig::Detailed(config) => Self {
                 url: config.url,
-                basic_auth: config.basic_auth,
+                auth: config.auth,
                 headers: config.headers,
+                custom_scheme: config.custom_scheme.map(Into::into),
             },
         }
     }
@@ -206,5 +535,13 @@ pub async fn load_config() -> crate::Result<Arc<Config>> {
 
     let config = Config::from_raw_config(raw_config).await?;
 
+    if let Err(e) = config.preflight().await {
+        tracing::error!(
+            "Startup preflight failed: {}",
+            crate::utils::pretty_error(&e)
+        );
+        return Err(e);
+    }
+
     Ok(Arc::new(config))
 }
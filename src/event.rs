@@ -1,5 +1,9 @@
-use crate::{ipdb, proxy::ProxyType};
+use crate::{
+    ipdb,
+    proxy::{Anonymity, ProxyType},
+};
 
+#[derive(Clone, serde::Serialize)]
 pub enum AppEvent {
     IpDbTotal(ipdb::DbType, Option<u64>),
     IpDbDownloaded(ipdb::DbType, usize),
@@ -11,6 +15,28 @@ pub enum AppEvent {
     ProxyChecked(ProxyType),
     ProxyWorking(ProxyType),
 
+    /// Emitted once per working proxy after
+    /// [`crate::proxy::Proxy::check`] resolves its exit IP and classifies
+    /// its anonymity, so the TUI/GUI can tally these per [`ProxyType`].
+    ProxyIdentity {
+        protocol: ProxyType,
+        exit_ip_resolved: bool,
+        anonymity: Option<Anonymity>,
+        proxy_protocol_supported: Option<bool>,
+    },
+
+    /// Full per-proxy detail reported once a proxy passes checking, feeding
+    /// the TUI's drill-down inspector view (see
+    /// [`crate::tui::WorkingProxyDetail`]).
+    ProxyWorkingDetails {
+        protocol: ProxyType,
+        addr: compact_str::CompactString,
+        latency_secs: f64,
+        country: Option<compact_str::CompactString>,
+        asn: Option<u32>,
+        anonymity: Option<Anonymity>,
+    },
+
     Done,
     Quit,
 }
@@ -1,15 +1,14 @@
 use std::{
     io,
-    net::SocketAddr,
+    net::{IpAddr, SocketAddr},
     sync::Arc,
-    time::{Duration, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
 
-use crate::config::Config;
+use base64::Engine as _;
+use color_eyre::eyre::{WrapErr as _, eyre};
 
-const DEFAULT_MAX_RETRIES: u32 = 2;
-const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(500);
-const MAX_RETRY_DELAY: Duration = Duration::from_secs(8);
+use crate::config::{Config, DohConfig};
 
 static RETRY_STATUSES: &[reqwest::StatusCode] = &[
     reqwest::StatusCode::REQUEST_TIMEOUT,
@@ -20,10 +19,23 @@ static RETRY_STATUSES: &[reqwest::StatusCode] = &[
     reqwest::StatusCode::GATEWAY_TIMEOUT,
 ];
 
+#[derive(Clone, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SourceAuth {
+    Basic { username: String, password: Option<String> },
+    Bearer { token: String },
+    Header { name: String, value: String },
+}
+
+/// Credentials for `scraping.proxy`, applied via
+/// [`reqwest::Proxy::basic_auth`] in [`create_reqwest_client`] so scraping
+/// can go through an authenticated forward proxy instead of only an
+/// anonymous one.
 #[derive(Clone, serde::Deserialize)]
 pub struct BasicAuth {
     pub username: String,
-    pub password: Option<String>,
+    #[serde(default)]
+    pub password: String,
 }
 
 pub struct HickoryDnsResolver(Arc<hickory_resolver::TokioResolver>);
@@ -62,6 +74,173 @@ impl reqwest::dns::Resolve for HickoryDnsResolver {
     }
 }
 
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+struct DohResolverState {
+    endpoint: url::Url,
+    client: reqwest::Client,
+    cache: parking_lot::Mutex<
+        lru::LruCache<compact_str::CompactString, (Vec<IpAddr>, Instant)>,
+    >,
+    overrides: crate::HashMap<compact_str::CompactString, Vec<IpAddr>>,
+}
+
+async fn doh_query(
+    client: &reqwest::Client,
+    endpoint: &url::Url,
+    name: &str,
+    record_type: hickory_resolver::proto::rr::RecordType,
+) -> Result<Vec<(IpAddr, Duration)>, BoxError> {
+    use hickory_resolver::proto::{
+        op::{Message, MessageType, OpCode, Query},
+        rr::{Name, RData},
+        serialize::binary::{BinDecodable as _, BinEncodable as _},
+    };
+
+    let mut message = Message::new();
+    message
+        .set_id(rand::random())
+        .set_message_type(MessageType::Query)
+        .set_op_code(OpCode::Query)
+        .set_recursion_desired(true);
+    message.add_query(Query::query(name.parse::<Name>()?, record_type));
+
+    let mut url = endpoint.clone();
+    url.query_pairs_mut().append_pair(
+        "dns",
+        &base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(message.to_vec()?),
+    );
+
+    let body = client
+        .get(url)
+        .header(reqwest::header::ACCEPT, "application/dns-message")
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    let answer = Message::from_bytes(&body)?;
+
+    Ok(answer
+        .answers()
+        .iter()
+        .filter_map(|record| {
+            let ttl = Duration::from_secs(u64::from(record.ttl()));
+            match record.data() {
+                RData::A(addr) => Some((IpAddr::V4(addr.0), ttl)),
+                RData::AAAA(addr) => Some((IpAddr::V6(addr.0), ttl)),
+                _ => None,
+            }
+        })
+        .collect())
+}
+
+impl DohResolverState {
+    fn cached(&self, name: &str) -> Option<Vec<IpAddr>> {
+        let mut cache = self.cache.lock();
+        let (addrs, expires_at) = cache.get(name)?;
+        if *expires_at <= Instant::now() {
+            cache.pop(name);
+            return None;
+        }
+        Some(addrs.clone())
+    }
+
+    async fn resolve(&self, name: &str) -> Result<Vec<IpAddr>, BoxError> {
+        if let Some(addrs) = self.overrides.get(name) {
+            return Ok(addrs.clone());
+        }
+
+        if let Some(addrs) = self.cached(name) {
+            return Ok(addrs);
+        }
+
+        let mut records = Vec::new();
+        for record_type in [
+            hickory_resolver::proto::rr::RecordType::A,
+            hickory_resolver::proto::rr::RecordType::AAAA,
+        ] {
+            records.extend(
+                doh_query(&self.client, &self.endpoint, name, record_type)
+                    .await?,
+            );
+        }
+
+        let ttl = records
+            .iter()
+            .map(|&(_, ttl)| ttl)
+            .min()
+            .unwrap_or(Duration::from_secs(60));
+        let addrs: Vec<IpAddr> =
+            records.into_iter().map(|(addr, _)| addr).collect();
+        self.cache
+            .lock()
+            .put(name.into(), (addrs.clone(), Instant::now() + ttl));
+        Ok(addrs)
+    }
+}
+
+/// Resolves hostnames over DNS-over-HTTPS (RFC 8484) instead of relying on
+/// the system resolver, so proxy checking isn't at the mercy of a
+/// DNS-poisoning local network. Static overrides are consulted first, then
+/// an LRU cache honoring the upstream TTL, falling back to the network.
+pub struct DohResolver(Arc<DohResolverState>);
+
+impl DohResolver {
+    pub fn new(config: &DohConfig) -> reqwest::Result<Self> {
+        Ok(Self(Arc::new(DohResolverState {
+            endpoint: config.endpoint.clone(),
+            client: reqwest::Client::builder().build()?,
+            cache: parking_lot::Mutex::new(lru::LruCache::new(
+                config.cache_size,
+            )),
+            overrides: config.overrides.clone(),
+        })))
+    }
+}
+
+impl reqwest::dns::Resolve for DohResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let state = Arc::clone(&self.0);
+        Box::pin(async move {
+            let addrs: reqwest::dns::Addrs = Box::new(
+                state
+                    .resolve(name.as_str())
+                    .await?
+                    .into_iter()
+                    .map(|ip_addr| SocketAddr::new(ip_addr, 0)),
+            );
+            Ok(addrs)
+        })
+    }
+}
+
+/// Picks between the system resolver and [`DohResolver`] at startup,
+/// depending on whether `[dns.doh]` is configured.
+pub enum DnsResolver {
+    System(HickoryDnsResolver),
+    Doh(DohResolver),
+}
+
+impl DnsResolver {
+    pub async fn new(config: &Config) -> crate::Result<Self> {
+        Ok(match &config.dns.doh {
+            Some(doh_config) => Self::Doh(DohResolver::new(doh_config)?),
+            None => Self::System(HickoryDnsResolver::new().await?),
+        })
+    }
+}
+
+impl reqwest::dns::Resolve for DnsResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        match self {
+            Self::System(resolver) => resolver.resolve(name),
+            Self::Doh(resolver) => resolver.resolve(name),
+        }
+    }
+}
+
 fn parse_retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
     if let Some(val) = headers.get("retry-after-ms")
         && let Ok(s) = val.to_str()
@@ -86,27 +265,165 @@ fn parse_retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
     None
 }
 
-fn calculate_retry_timeout(
-    headers: Option<&reqwest::header::HeaderMap>,
-    attempt: u32,
-) -> Option<Duration> {
-    if let Some(h) = headers
-        && let Some(after) = parse_retry_after(h)
-    {
-        if after > Duration::from_secs(60) {
-            return None;
+/// Ceiling on how long a tripped circuit ever stays open, no matter how
+/// many consecutive times the same host has tripped it.
+const MAX_CIRCUIT_COOLDOWN: Duration = Duration::from_secs(300);
+
+#[derive(Clone, Copy)]
+enum CircuitPhase {
+    Closed,
+    Open { until: Instant },
+    HalfOpen,
+}
+
+struct HostState {
+    consecutive_failures: usize,
+    trip_count: u32,
+    phase: CircuitPhase,
+}
+
+impl Default for HostState {
+    fn default() -> Self {
+        Self {
+            consecutive_failures: 0,
+            trip_count: 0,
+            phase: CircuitPhase::Closed,
         }
-        return Some(after);
     }
+}
+
+/// Returned instead of issuing a request while a host's circuit is open, so
+/// the failure shows up as a clearly-labelled fast-fail rather than another
+/// real connection timeout.
+#[derive(Debug)]
+struct CircuitOpenError {
+    host: compact_str::CompactString,
+}
 
-    let base = INITIAL_RETRY_DELAY
-        .saturating_mul(2_u32.pow(attempt))
-        .min(MAX_RETRY_DELAY);
-    let jitter = 0.25_f64.mul_add(-rand::random::<f64>(), 1.0);
-    Some(base.mul_f64(jitter))
+impl std::fmt::Display for CircuitOpenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "circuit breaker is open for host {:?}, skipping request",
+            self.host
+        )
+    }
 }
 
-pub struct RetryMiddleware;
+impl std::error::Error for CircuitOpenError {}
+
+/// Per-host circuit breaker shared across a [`RetryMiddleware`] instance,
+/// so one unreachable scraping source stops being hammered with retries
+/// instead of stalling the rest of the scrape.
+struct CircuitBreaker {
+    failure_threshold: usize,
+    base_cooldown: Duration,
+    hosts: parking_lot::Mutex<crate::HashMap<compact_str::CompactString, HostState>>,
+}
+
+impl CircuitBreaker {
+    fn new(config: &crate::config::CircuitBreakerConfig) -> Self {
+        Self {
+            failure_threshold: config.failure_threshold.get(),
+            base_cooldown: config.cooldown,
+            hosts: parking_lot::Mutex::new(crate::HashMap::default()),
+        }
+    }
+
+    /// Called before issuing a request to `host`. Lets the request through
+    /// unless the circuit is still open; an expired `Open` circuit
+    /// transitions to `HalfOpen` and lets the request through as a probe.
+    /// A `HalfOpen` circuit does not hold back other requests: every request
+    /// is let through until the next recorded outcome closes or re-opens it.
+    fn check(&self, host: &str) -> Result<(), CircuitOpenError> {
+        let mut hosts = self.hosts.lock();
+        let state = hosts.entry(host.into()).or_default();
+        match state.phase {
+            CircuitPhase::Closed | CircuitPhase::HalfOpen => Ok(()),
+            CircuitPhase::Open { until } => {
+                if Instant::now() >= until {
+                    state.phase = CircuitPhase::HalfOpen;
+                    Ok(())
+                } else {
+                    Err(CircuitOpenError { host: host.into() })
+                }
+            }
+        }
+    }
+
+    fn record_outcome(&self, host: &str, success: bool) {
+        let mut hosts = self.hosts.lock();
+        let state = hosts.entry(host.into()).or_default();
+        if success {
+            *state = HostState::default();
+            return;
+        }
+
+        state.consecutive_failures =
+            state.consecutive_failures.saturating_add(1);
+        let should_trip = matches!(state.phase, CircuitPhase::HalfOpen)
+            || state.consecutive_failures >= self.failure_threshold;
+        if should_trip {
+            let cooldown = self
+                .base_cooldown
+                .saturating_mul(2_u32.saturating_pow(state.trip_count))
+                .min(MAX_CIRCUIT_COOLDOWN);
+            state.trip_count = state.trip_count.saturating_add(1);
+            state.phase =
+                CircuitPhase::Open { until: Instant::now() + cooldown };
+        }
+    }
+}
+
+pub struct RetryMiddleware {
+    max_retries: u32,
+    initial_delay: Duration,
+    max_delay: Duration,
+    jitter_fraction: f64,
+    retryable_statuses: Vec<reqwest::StatusCode>,
+    circuit_breaker: CircuitBreaker,
+}
+
+impl RetryMiddleware {
+    pub fn new(config: &crate::config::RetryConfig) -> Self {
+        let retryable_statuses = RETRY_STATUSES
+            .iter()
+            .copied()
+            .chain(config.extra_retryable_statuses.iter().copied())
+            .collect();
+        Self {
+            max_retries: u32::try_from(config.max_retries)
+                .unwrap_or(u32::MAX),
+            initial_delay: config.initial_delay,
+            max_delay: config.max_delay,
+            jitter_fraction: config.jitter_fraction,
+            retryable_statuses,
+            circuit_breaker: CircuitBreaker::new(&config.circuit_breaker),
+        }
+    }
+
+    fn calculate_retry_timeout(
+        &self,
+        headers: Option<&reqwest::header::HeaderMap>,
+        attempt: u32,
+    ) -> Option<Duration> {
+        if let Some(h) = headers
+            && let Some(after) = parse_retry_after(h)
+        {
+            if after > Duration::from_secs(60) {
+                return None;
+            }
+            return Some(after);
+        }
+
+        let base = self
+            .initial_delay
+            .saturating_mul(2_u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        let jitter = self.jitter_fraction.mul_add(-rand::random::<f64>(), 1.0);
+        Some(base.mul_f64(jitter))
+    }
+}
 
 #[async_trait::async_trait]
 impl reqwest_middleware::Middleware for RetryMiddleware {
@@ -116,6 +433,15 @@ impl reqwest_middleware::Middleware for RetryMiddleware {
         extensions: &mut http::Extensions,
         next: reqwest_middleware::Next<'_>,
     ) -> reqwest_middleware::Result<reqwest::Response> {
+        let host = req.url().host_str().map(compact_str::CompactString::from);
+        if let Some(host) = &host {
+            self.circuit_breaker.check(host).map_err(|e| {
+                reqwest_middleware::Error::middleware(io::Error::other(
+                    e.to_string(),
+                ))
+            })?;
+        }
+
         let mut attempt: u32 = 0;
         loop {
             let req = req.try_clone().ok_or_else(|| {
@@ -128,9 +454,9 @@ impl reqwest_middleware::Middleware for RetryMiddleware {
                 Ok(resp) => {
                     let status = resp.status();
                     if status.is_client_error() || status.is_server_error() {
-                        if attempt < DEFAULT_MAX_RETRIES
-                            && RETRY_STATUSES.contains(&status)
-                            && let Some(delay) = calculate_retry_timeout(
+                        if attempt < self.max_retries
+                            && self.retryable_statuses.contains(&status)
+                            && let Some(delay) = self.calculate_retry_timeout(
                                 Some(resp.headers()),
                                 attempt,
                             )
@@ -139,20 +465,33 @@ impl reqwest_middleware::Middleware for RetryMiddleware {
                             attempt = attempt.saturating_add(1);
                             continue;
                         }
+                        if let Some(host) = &host {
+                            self.circuit_breaker.record_outcome(
+                                host,
+                                !status.is_server_error(),
+                            );
+                        }
                         resp.error_for_status_ref()?;
                     }
+                    if let Some(host) = &host {
+                        self.circuit_breaker.record_outcome(host, true);
+                    }
                     return Ok(resp);
                 }
                 Err(err) => {
-                    if attempt < DEFAULT_MAX_RETRIES
+                    if attempt < self.max_retries
                         && err.is_connect()
                         && let Some(delay) =
-                            calculate_retry_timeout(None, attempt)
+                            self.calculate_retry_timeout(None, attempt)
                     {
                         tokio::time::sleep(delay).await;
                         attempt = attempt.saturating_add(1);
                         continue;
                     }
+                    if let Some(host) = &host {
+                        self.circuit_breaker
+                            .record_outcome(host, !err.is_connect());
+                    }
                     return Err(err);
                 }
             }
@@ -163,20 +502,72 @@ impl reqwest_middleware::Middleware for RetryMiddleware {
 pub fn create_reqwest_client<R: reqwest::dns::Resolve + 'static>(
     config: &Config,
     dns_resolver: Arc<R>,
-) -> reqwest::Result<reqwest_middleware::ClientWithMiddleware> {
+) -> crate::Result<reqwest_middleware::ClientWithMiddleware> {
     let mut builder = reqwest::ClientBuilder::new()
         .user_agent(&config.scraping.user_agent)
         .timeout(config.scraping.timeout)
         .connect_timeout(config.scraping.connect_timeout)
         .dns_resolver(dns_resolver);
 
+    builder = match config.tls.backend {
+        crate::tls::TlsBackend::Rustls => {
+            let builder = builder.use_rustls_tls();
+            match config.tls.root_store {
+                crate::tls::TlsRootStore::Native => {
+                    builder.tls_built_in_native_certs(true)
+                }
+                crate::tls::TlsRootStore::WebpkiBundled => {
+                    builder.tls_built_in_webpki_certs(true)
+                }
+            }
+        }
+        crate::tls::TlsBackend::NativeTls => {
+            #[cfg(feature = "native-tls")]
+            {
+                builder.use_native_tls()
+            }
+            #[cfg(not(feature = "native-tls"))]
+            return Err(eyre!(
+                "tls.backend = \"nativetls\" requires building with the \
+                 `native-tls` feature enabled"
+            ));
+        }
+    };
+
+    for ca_cert_path in &config.tls.extra_ca_certs {
+        let pem = std::fs::read(ca_cert_path).wrap_err_with(|| {
+            format!(
+                "failed to read tls.extra_ca_certs entry: {}",
+                ca_cert_path.display()
+            )
+        })?;
+        builder =
+            builder.add_root_certificate(reqwest::Certificate::from_pem(&pem)?);
+    }
+
+    if let Some(upstream_proxy) = &config.upstream_proxy {
+        builder = builder.connector_layer(
+            crate::socks::UpstreamSocksLayer::new(Arc::clone(upstream_proxy)),
+        );
+    }
+
     if let Some(proxy) = &config.scraping.proxy {
-        builder = builder.proxy(reqwest::Proxy::all(proxy.clone())?);
+        let mut reqwest_proxy = reqwest::Proxy::all(proxy.clone())?;
+        if let Some(auth) = &config.scraping.proxy_auth {
+            reqwest_proxy =
+                reqwest_proxy.basic_auth(&auth.username, &auth.password);
+        } else if !proxy.username().is_empty() || proxy.password().is_some() {
+            reqwest_proxy = reqwest_proxy.basic_auth(
+                proxy.username(),
+                proxy.password().unwrap_or_default(),
+            );
+        }
+        builder = builder.proxy(reqwest_proxy);
     }
 
     let client = builder.build()?;
     let client_with_middleware = reqwest_middleware::ClientBuilder::new(client)
-        .with(RetryMiddleware)
+        .with(RetryMiddleware::new(&config.scraping.retry))
         .build();
 
     Ok(client_with_middleware)
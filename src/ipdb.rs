@@ -1,4 +1,9 @@
-use std::{io, path::PathBuf};
+use std::{
+    borrow::Cow,
+    io::{self, Write as _},
+    net::IpAddr,
+    path::PathBuf,
+};
 
 use color_eyre::eyre::{WrapErr as _, eyre};
 use tokio::io::AsyncWriteExt as _;
@@ -7,7 +12,114 @@ use tokio::io::AsyncWriteExt as _;
 use crate::event::{AppEvent, Event};
 use crate::{fs::get_cache_path, utils::is_docker};
 
+/// Parses the `<total>` out of a `Content-Range: bytes <start>-<end>/<total>`
+/// response header, giving the true artifact size on a `206 Partial Content`
+/// response, where `Content-Length` only covers the remaining bytes.
+fn content_range_total(response: &reqwest::Response) -> Option<u64> {
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .rsplit_once('/')?
+        .1
+        .parse()
+        .ok()
+}
+
+/// Which compression, if any, wraps a downloaded database artifact.
+/// [`Self::from_content_encoding`] is authoritative when the server sets
+/// it; otherwise [`Self::from_url`] falls back to the `.gz`/`.zst` suffix
+/// of the download URL, so a configured
+/// [`crate::config::GeoIpSource::Url`] can point at a compressed mirror
+/// while the decompressed bytes still land in the usual cached `.mmdb`.
 #[derive(Clone, Copy)]
+enum ArtifactCompression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl ArtifactCompression {
+    fn from_url(url: &str) -> Self {
+        if url.ends_with(".gz") {
+            Self::Gzip
+        } else if url.ends_with(".zst") {
+            Self::Zstd
+        } else {
+            Self::None
+        }
+    }
+
+    fn from_content_encoding(response: &reqwest::Response) -> Self {
+        match response
+            .headers()
+            .get(reqwest::header::CONTENT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+        {
+            Some("gzip" | "x-gzip") => Self::Gzip,
+            Some("zstd") => Self::Zstd,
+            _ => Self::None,
+        }
+    }
+
+    const fn is_none(self) -> bool {
+        matches!(self, Self::None)
+    }
+}
+
+/// Incrementally decompresses response chunks as they arrive, so
+/// [`DbType::save_db`] only ever holds one chunk's worth of compressed and
+/// decompressed data in memory instead of buffering the whole artifact.
+enum ChunkDecoder {
+    None,
+    Gzip(Box<flate2::write::GzDecoder<Vec<u8>>>),
+    Zstd(Box<zstd::stream::write::Decoder<'static, Vec<u8>>>),
+}
+
+impl ChunkDecoder {
+    fn new(compression: ArtifactCompression) -> color_eyre::Result<Self> {
+        Ok(match compression {
+            ArtifactCompression::None => Self::None,
+            ArtifactCompression::Gzip => {
+                Self::Gzip(Box::new(flate2::write::GzDecoder::new(Vec::new())))
+            }
+            ArtifactCompression::Zstd => Self::Zstd(Box::new(
+                zstd::stream::write::Decoder::new(Vec::new())
+                    .wrap_err("failed to initialize zstd decoder")?,
+            )),
+        })
+    }
+
+    /// Feeds a chunk of compressed response bytes through the decoder,
+    /// returning the decompressed bytes it released as a result. Borrows
+    /// `chunk` directly when no decompression is needed.
+    fn push<'c>(&mut self, chunk: &'c [u8]) -> io::Result<Cow<'c, [u8]>> {
+        match self {
+            Self::None => Ok(Cow::Borrowed(chunk)),
+            Self::Gzip(decoder) => {
+                decoder.write_all(chunk)?;
+                Ok(Cow::Owned(std::mem::take(decoder.get_mut())))
+            }
+            Self::Zstd(decoder) => {
+                decoder.write_all(chunk)?;
+                Ok(Cow::Owned(std::mem::take(decoder.get_mut())))
+            }
+        }
+    }
+
+    /// Flushes any decompressed bytes still buffered inside the decoder
+    /// once the compressed stream has ended.
+    fn finish(self) -> io::Result<Vec<u8>> {
+        match self {
+            Self::None => Ok(Vec::new()),
+            Self::Gzip(decoder) => decoder.finish(),
+            Self::Zstd(decoder) => decoder.finish(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, serde::Serialize)]
 pub enum DbType {
     Asn,
     Geo,
@@ -46,22 +158,209 @@ impl DbType {
         Ok(db_path)
     }
 
+    /// Where the artifact is streamed to before it's known to be complete
+    /// and intact, so a crash or cancellation mid-download never leaves a
+    /// truncated file at [`Self::db_path`]. Left in place on cancellation so
+    /// the next run can resume it with an HTTP Range request.
+    async fn partial_path(self) -> color_eyre::Result<PathBuf> {
+        let mut db_path = self.db_path().await.wrap_err_with(move || {
+            format!("failed to get {} database path", self.name())
+        })?;
+        db_path.set_extension("mmdb.partial");
+        Ok(db_path)
+    }
+
+    /// ETag of the in-progress download [`Self::partial_path`] belongs to,
+    /// sent back as `If-Range` when resuming so a server that rotated the
+    /// resource in the meantime is detected instead of Frankensteining a
+    /// file out of two different versions.
+    async fn partial_etag_path(self) -> color_eyre::Result<PathBuf> {
+        let mut db_path = self.db_path().await.wrap_err_with(move || {
+            format!("failed to get {} database path", self.name())
+        })?;
+        db_path.set_extension("mmdb.partial.etag");
+        Ok(db_path)
+    }
+
+    async fn save_partial_etag(
+        self,
+        etag: impl AsRef<[u8]>,
+    ) -> color_eyre::Result<()> {
+        let path = self.partial_etag_path().await?;
+        tokio::fs::write(&path, etag).await.wrap_err_with(move || {
+            format!("failed to write to file {}", path.display())
+        })
+    }
+
+    async fn read_partial_etag(
+        self,
+    ) -> color_eyre::Result<Option<reqwest::header::HeaderValue>> {
+        let path = self.partial_etag_path().await?;
+        match tokio::fs::read_to_string(&path).await {
+            Ok(text) => Ok(text.parse().ok()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).wrap_err_with(move || {
+                format!("failed to read file {} to string", path.display())
+            }),
+        }
+    }
+
+    async fn remove_partial(self) -> color_eyre::Result<()> {
+        let partial_path = self.partial_path().await?;
+        match tokio::fs::remove_file(&partial_path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => {
+                return Err(e).wrap_err_with(move || {
+                    format!("failed to remove {}", partial_path.display())
+                });
+            }
+        }?;
+        let partial_etag_path = self.partial_etag_path().await?;
+        match tokio::fs::remove_file(&partial_etag_path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e).wrap_err_with(move || {
+                format!("failed to remove {}", partial_etag_path.display())
+            }),
+        }
+    }
+
+    /// BLAKE3 digest of the last successfully verified [`Self::db_path`]
+    /// contents, so a truncated or bit-rotted cache file is detected at
+    /// startup instead of crashing the mmdb reader.
+    async fn hash_path(self) -> color_eyre::Result<PathBuf> {
+        let mut db_path = self.db_path().await.wrap_err_with(move || {
+            format!("failed to get {} database path", self.name())
+        })?;
+        db_path.set_extension("mmdb.blake3");
+        Ok(db_path)
+    }
+
+    async fn save_hash(self, hash: &blake3::Hash) -> color_eyre::Result<()> {
+        let path = self.hash_path().await?;
+        tokio::fs::write(&path, hash.to_hex().as_bytes()).await.wrap_err_with(
+            move || format!("failed to write to file {}", path.display()),
+        )
+    }
+
+    async fn read_hash(self) -> color_eyre::Result<Option<blake3::Hash>> {
+        let path = self.hash_path().await?;
+        match tokio::fs::read_to_string(&path).await {
+            Ok(text) => Ok(text.trim().parse().ok()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).wrap_err_with(move || {
+                format!("failed to read file {} to string", path.display())
+            }),
+        }
+    }
+
+    /// Hashes the cached [`Self::db_path`] file and compares it against the
+    /// digest recorded by the last successful [`Self::save_db`], so a cache
+    /// corrupted after the fact (disk bitrot, an out-of-band edit) is caught
+    /// before it's handed to the mmdb reader instead of being trusted
+    /// because an ETag still matches.
+    async fn cache_is_intact(self) -> color_eyre::Result<bool> {
+        let Some(expected) = self.read_hash().await? else {
+            return Ok(false);
+        };
+        let db_path = self.db_path().await?;
+        let bytes = match tokio::fs::read(&db_path).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => {
+                return Err(e).wrap_err_with(move || {
+                    format!("failed to read file {}", db_path.display())
+                });
+            }
+        };
+        let actual = tokio::task::spawn_blocking(move || blake3::hash(&bytes))
+            .await
+            .wrap_err("failed to spawn tokio blocking task")?;
+        Ok(actual == expected)
+    }
+
+    /// Fetches `{url}.sha256`, the checksum sidecar convention published
+    /// alongside the mmdb artifacts, returning `None` if it's absent or
+    /// unreachable (the sidecar is an optional cross-check, not a
+    /// requirement).
+    async fn fetch_checksum_sidecar(
+        self,
+        http_client: &reqwest::Client,
+        url: &str,
+    ) -> Option<compact_str::CompactString> {
+        let sidecar_url = format!("{url}.sha256");
+        let response =
+            http_client.get(sidecar_url).send().await.ok()?.error_for_status().ok()?;
+        let text = response.text().await.ok()?;
+        text.split_whitespace().next().map(Into::into)
+    }
+
     async fn save_db(
         self,
         mut response: reqwest::Response,
+        expected_sha256: Option<compact_str::CompactString>,
+        compression: ArtifactCompression,
+        resume_offset: u64,
         #[cfg(feature = "tui")] tx: tokio::sync::mpsc::UnboundedSender<Event>,
     ) -> color_eyre::Result<()> {
         #[cfg(feature = "tui")]
         drop(tx.send(Event::App(AppEvent::IpDbTotal(
             self,
-            response.content_length(),
+            content_range_total(&response)
+                .or_else(|| response.content_length().map(|l| l + resume_offset)),
         ))));
 
-        let db_path = self.db_path().await?;
-        let mut file =
-            tokio::fs::File::create(&db_path).await.wrap_err_with(|| {
-                format!("failed to create file {}", db_path.display())
+        let partial_path = self.partial_path().await?;
+        let mut blake3_hasher = blake3::Hasher::new();
+        let mut sha256_hasher =
+            expected_sha256.is_some().then(sha2::Sha256::new);
+        let mut chunk_decoder =
+            ChunkDecoder::new(compression).wrap_err_with(|| {
+                format!(
+                    "failed to initialize decompressor for {} database",
+                    self.name()
+                )
             })?;
+
+        let mut file = if resume_offset > 0 {
+            let existing =
+                tokio::fs::read(&partial_path).await.wrap_err_with(|| {
+                    format!(
+                        "failed to read file {}",
+                        partial_path.display()
+                    )
+                })?;
+            blake3_hasher.update(&existing);
+            if let Some(sha256_hasher) = sha256_hasher.as_mut() {
+                sha2::Digest::update(sha256_hasher, &existing);
+            }
+            #[cfg(feature = "tui")]
+            drop(tx.send(Event::App(AppEvent::IpDbDownloaded(
+                self,
+                existing.len(),
+            ))));
+            drop(existing);
+            tokio::fs::OpenOptions::new()
+                .append(true)
+                .open(&partial_path)
+                .await
+                .wrap_err_with(|| {
+                    format!(
+                        "failed to open file {}",
+                        partial_path.display()
+                    )
+                })?
+        } else {
+            tokio::fs::File::create(&partial_path).await.wrap_err_with(
+                || {
+                    format!(
+                        "failed to create file {}",
+                        partial_path.display()
+                    )
+                },
+            )?
+        };
         while let Some(chunk) =
             response.chunk().await.wrap_err_with(move || {
                 format!(
@@ -70,18 +369,95 @@ impl DbType {
                 )
             })?
         {
-            file.write_all(&chunk).await.wrap_err_with(|| {
-                format!("failed to write to file {}", db_path.display())
+            if let Some(sha256_hasher) = sha256_hasher.as_mut() {
+                sha2::Digest::update(sha256_hasher, &chunk);
+            }
+            let decompressed =
+                chunk_decoder.push(&chunk).wrap_err_with(|| {
+                    format!(
+                        "failed to decompress {} database response chunk",
+                        self.name()
+                    )
+                })?;
+            file.write_all(&decompressed).await.wrap_err_with(|| {
+                format!("failed to write to file {}", partial_path.display())
             })?;
+            blake3_hasher.update(&decompressed);
             #[cfg(feature = "tui")]
             drop(
                 tx.send(Event::App(AppEvent::IpDbDownloaded(
                     self,
-                    chunk.len(),
+                    decompressed.len(),
                 ))),
             );
         }
-        Ok(())
+
+        let trailing = chunk_decoder.finish().wrap_err_with(|| {
+            format!("failed to finish decompressing {} database", self.name())
+        })?;
+        if !trailing.is_empty() {
+            file.write_all(&trailing).await.wrap_err_with(|| {
+                format!("failed to write to file {}", partial_path.display())
+            })?;
+            blake3_hasher.update(&trailing);
+            #[cfg(feature = "tui")]
+            drop(tx.send(Event::App(AppEvent::IpDbDownloaded(
+                self,
+                trailing.len(),
+            ))));
+        }
+
+        file.flush().await.wrap_err_with(|| {
+            format!("failed to flush file {}", partial_path.display())
+        })?;
+        drop(file);
+
+        if let (Some(expected), Some(sha256_hasher)) =
+            (&expected_sha256, sha256_hasher)
+        {
+            let actual: String = sha2::Digest::finalize(sha256_hasher)
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect();
+            if !expected.eq_ignore_ascii_case(&actual) {
+                self.remove_partial().await.wrap_err_with(move || {
+                    format!(
+                        "failed to remove partial {} database",
+                        self.name()
+                    )
+                })?;
+                return Err(eyre!(
+                    "{} database checksum mismatch: sidecar says {expected}, \
+                     downloaded file hashes to {actual}",
+                    self.name()
+                ));
+            }
+        }
+
+        let blake3_hash = blake3_hasher.finalize();
+        let db_path = self.db_path().await?;
+        tokio::fs::rename(&partial_path, &db_path).await.wrap_err_with(
+            || {
+                format!(
+                    "failed to move {} into place at {}",
+                    partial_path.display(),
+                    db_path.display()
+                )
+            },
+        )?;
+        let partial_etag_path = self.partial_etag_path().await?;
+        match tokio::fs::remove_file(&partial_etag_path).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(e).wrap_err_with(move || {
+                    format!("failed to remove {}", partial_etag_path.display())
+                });
+            }
+        }
+        self.save_hash(&blake3_hash).await.wrap_err_with(move || {
+            format!("failed to save {} database hash", self.name())
+        })
     }
 
     async fn save_etag(self, etag: impl AsRef<[u8]>) -> color_eyre::Result<()> {
@@ -115,24 +491,67 @@ impl DbType {
         }
     }
 
-    pub async fn download(
+    /// Streams a plain (optionally `.gz`/`.zst`-compressed) `.mmdb` artifact
+    /// from `url`, with the same resumable-partial, ETag and checksum-sidecar
+    /// handling as the built-in mirror. Used for both [`Self::download`]'s
+    /// `None` (built-in mirror) and [`crate::config::GeoIpSource::Url`]
+    /// cases.
+    async fn download_from_url(
         self,
         http_client: reqwest::Client,
+        url: &str,
         #[cfg(feature = "tui")] tx: tokio::sync::mpsc::UnboundedSender<Event>,
     ) -> color_eyre::Result<()> {
         let db_path = self.db_path().await?;
+        let partial_path = self.partial_path().await?;
         let mut headers = reqwest::header::HeaderMap::new();
-        #[expect(clippy::collapsible_if)]
+        let mut resume_offset: u64 = 0;
+        // A compressed partial can't be resumed by byte offset: the Range
+        // request resumes the compressed stream, but the decoder has
+        // already consumed and discarded its internal state once the
+        // process exits, so resuming would need to replay from the start
+        // of the compressed stream anyway. Always restart those instead.
+        let url_compression = ArtifactCompression::from_url(url);
+
         if tokio::fs::metadata(&db_path).await.is_ok_and(|m| m.is_file()) {
-            if let Some(etag) =
-                self.read_etag().await.wrap_err("failed to read ETag")?
-            {
-                headers.insert(reqwest::header::IF_NONE_MATCH, etag);
+            if self.cache_is_intact().await.wrap_err_with(move || {
+                format!("failed to verify cached {} database", self.name())
+            })? {
+                if let Some(etag) =
+                    self.read_etag().await.wrap_err("failed to read ETag")?
+                {
+                    headers.insert(reqwest::header::IF_NONE_MATCH, etag);
+                }
+            } else {
+                tracing::warn!(
+                    "Cached {} database at {} failed integrity \
+                     verification; re-downloading",
+                    self.name(),
+                    db_path.display()
+                );
             }
+        } else if url_compression.is_none()
+            && let Ok(partial_meta) =
+                tokio::fs::metadata(&partial_path).await
+            && partial_meta.is_file()
+            && partial_meta.len() > 0
+            && let Some(partial_etag) = self
+                .read_partial_etag()
+                .await
+                .wrap_err("failed to read partial ETag")?
+        {
+            resume_offset = partial_meta.len();
+            headers.insert(
+                reqwest::header::RANGE,
+                format!("bytes={resume_offset}-").parse().wrap_err(
+                    "failed to build Range header",
+                )?,
+            );
+            headers.insert(reqwest::header::IF_RANGE, partial_etag);
         }
 
         let response = http_client
-            .get(self.url())
+            .get(url)
             .headers(headers)
             .send()
             .await
@@ -159,7 +578,19 @@ impl DbType {
             return Ok(());
         }
 
-        if response.status() != reqwest::StatusCode::OK {
+        // A server that ignores Range and sends the whole resource again
+        // means we can't append to what's already on disk.
+        let resume_offset = if response.status()
+            == reqwest::StatusCode::PARTIAL_CONTENT
+        {
+            resume_offset
+        } else {
+            0
+        };
+
+        if response.status() != reqwest::StatusCode::OK
+            && response.status() != reqwest::StatusCode::PARTIAL_CONTENT
+        {
             return Err(eyre!(
                 "HTTP status error ({}) for url ({})",
                 response.status(),
@@ -168,9 +599,26 @@ impl DbType {
         }
 
         let etag = response.headers().get(reqwest::header::ETAG).cloned();
+        if resume_offset == 0
+            && let Some(etag) = &etag
+        {
+            self.save_partial_etag(etag).await.wrap_err_with(move || {
+                format!("failed to save partial {} database ETag", self.name())
+            })?;
+        }
+        let expected_sha256 =
+            self.fetch_checksum_sidecar(&http_client, url).await;
+        let compression =
+            match ArtifactCompression::from_content_encoding(&response) {
+                ArtifactCompression::None => url_compression,
+                detected => detected,
+            };
 
         self.save_db(
             response,
+            expected_sha256,
+            compression,
+            resume_offset,
             #[cfg(feature = "tui")]
             tx.clone(),
         )
@@ -204,10 +652,243 @@ impl DbType {
         }
     }
 
+    const fn maxmind_edition_id(self) -> &'static str {
+        match self {
+            Self::Asn => "GeoLite2-ASN",
+            Self::Geo => "GeoLite2-City",
+        }
+    }
+
+    /// Sidecar recording the modification time (as Unix seconds) of the
+    /// local `.mmdb` a [`crate::config::GeoIpSource::Path`] last pointed at,
+    /// so [`Self::sync_local_path`] only logs when the file has actually
+    /// changed since the last run.
+    async fn local_mtime_path(self) -> color_eyre::Result<PathBuf> {
+        let mut db_path = self.db_path().await.wrap_err_with(move || {
+            format!("failed to get {} database path", self.name())
+        })?;
+        db_path.set_extension("mmdb.localmtime");
+        Ok(db_path)
+    }
+
+    /// Local-path mode: no network fetch, just confirm the configured file
+    /// exists and log when its modification time has changed since the
+    /// last run.
+    async fn sync_local_path(
+        self,
+        path: &std::path::Path,
+    ) -> color_eyre::Result<()> {
+        let metadata = tokio::fs::metadata(path).await.wrap_err_with(|| {
+            format!(
+                "failed to read metadata of {} database at {}",
+                self.name(),
+                path.display()
+            )
+        })?;
+        let modified = metadata.modified().wrap_err_with(|| {
+            format!(
+                "failed to get modification time of {}",
+                path.display()
+            )
+        })?;
+        let modified_secs = modified
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+
+        let mtime_path = self.local_mtime_path().await?;
+        let previous_secs = tokio::fs::read_to_string(&mtime_path)
+            .await
+            .ok()
+            .and_then(|text| text.trim().parse::<u64>().ok());
+
+        if previous_secs != Some(modified_secs) {
+            tracing::info!(
+                "Using local {} database at {}",
+                self.name(),
+                path.display()
+            );
+            tokio::fs::write(&mtime_path, modified_secs.to_string())
+                .await
+                .wrap_err_with(|| {
+                    format!(
+                        "failed to write to file {}",
+                        mtime_path.display()
+                    )
+                })?;
+        }
+        Ok(())
+    }
+
+    /// MaxMind's `download` endpoint serves a `.tar.gz` containing a dated
+    /// directory with the `.mmdb` inside, rather than the bare file the
+    /// built-in mirror and [`Self::download_from_url`] expect, so this
+    /// fetches the whole archive into memory (these databases are only a
+    /// few MB), unpacks it, and installs the extracted `.mmdb` the same way
+    /// [`Self::save_db`] does.
+    async fn download_maxmind(
+        self,
+        http_client: reqwest::Client,
+        account_id: &str,
+        license_key: &str,
+        #[cfg(feature = "tui")] tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) -> color_eyre::Result<()> {
+        let response = http_client
+            .get(format!(
+                "https://download.maxmind.com/geoip/databases/{}/download?suffix=tar.gz",
+                self.maxmind_edition_id()
+            ))
+            .basic_auth(account_id, Some(license_key))
+            .send()
+            .await
+            .wrap_err_with(move || {
+                format!(
+                    "failed to send {} database download request",
+                    self.name()
+                )
+            })?
+            .error_for_status()
+            .wrap_err_with(move || {
+                format!(
+                    "got error HTTP status code when downloading {} database",
+                    self.name()
+                )
+            })?;
+
+        #[cfg(feature = "tui")]
+        drop(tx.send(Event::App(AppEvent::IpDbTotal(
+            self,
+            response.content_length(),
+        ))));
+
+        let archive_bytes = response.bytes().await.wrap_err_with(move || {
+            format!("failed to read {} database response body", self.name())
+        })?;
+        #[cfg(feature = "tui")]
+        drop(tx.send(Event::App(AppEvent::IpDbDownloaded(
+            self,
+            archive_bytes.len(),
+        ))));
+
+        let name = self.name();
+        let mmdb_bytes = tokio::task::spawn_blocking(move || {
+            let gunzipped = flate2::read::GzDecoder::new(&archive_bytes[..]);
+            let mut archive = tar::Archive::new(gunzipped);
+            for entry in
+                archive.entries().wrap_err("failed to read tar archive")?
+            {
+                let mut entry =
+                    entry.wrap_err("failed to read tar archive entry")?;
+                let entry_path = entry
+                    .path()
+                    .wrap_err("failed to read tar archive entry path")?;
+                if entry_path.extension().and_then(|e| e.to_str())
+                    == Some("mmdb")
+                {
+                    let mut buf = Vec::new();
+                    io::Read::read_to_end(&mut entry, &mut buf)
+                        .wrap_err("failed to read .mmdb tar entry")?;
+                    return Ok(buf);
+                }
+            }
+            Err(eyre!("{name} database archive did not contain an .mmdb file"))
+        })
+        .await
+        .wrap_err("failed to spawn tokio blocking task")??;
+
+        let blake3_hash = blake3::hash(&mmdb_bytes);
+        let partial_path = self.partial_path().await?;
+        tokio::fs::write(&partial_path, &mmdb_bytes).await.wrap_err_with(
+            || format!("failed to write to file {}", partial_path.display()),
+        )?;
+        let db_path = self.db_path().await?;
+        tokio::fs::rename(&partial_path, &db_path).await.wrap_err_with(
+            || {
+                format!(
+                    "failed to move {} into place at {}",
+                    partial_path.display(),
+                    db_path.display()
+                )
+            },
+        )?;
+        self.save_hash(&blake3_hash).await.wrap_err_with(move || {
+            format!("failed to save {} database hash", self.name())
+        })?;
+        // MaxMind's download endpoint doesn't serve an `ETag` we can reuse
+        // across runs, so there's nothing cached to compare against.
+        self.remove_etag().await.wrap_err_with(move || {
+            format!("failed to remove {} database ETag", self.name())
+        })?;
+
+        if is_docker().await {
+            tracing::info!(
+                "Downloaded {} database to Docker volume ({} in container)",
+                self.name(),
+                db_path.display()
+            );
+        } else {
+            tracing::info!(
+                "Downloaded {} database to {}",
+                self.name(),
+                db_path.display()
+            );
+        }
+        Ok(())
+    }
+
+    pub async fn download(
+        self,
+        http_client: reqwest::Client,
+        source: Option<&crate::config::GeoIpSource>,
+        #[cfg(feature = "tui")] tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    ) -> color_eyre::Result<()> {
+        match source {
+            Some(crate::config::GeoIpSource::Path(path)) => {
+                self.sync_local_path(path).await
+            }
+            Some(crate::config::GeoIpSource::Maxmind {
+                account_id,
+                license_key,
+            }) => {
+                self.download_maxmind(
+                    http_client,
+                    account_id,
+                    license_key,
+                    #[cfg(feature = "tui")]
+                    tx,
+                )
+                .await
+            }
+            Some(crate::config::GeoIpSource::Url(url)) => {
+                self.download_from_url(
+                    http_client,
+                    url,
+                    #[cfg(feature = "tui")]
+                    tx,
+                )
+                .await
+            }
+            None => {
+                self.download_from_url(
+                    http_client,
+                    self.url(),
+                    #[cfg(feature = "tui")]
+                    tx,
+                )
+                .await
+            }
+        }
+    }
+
     pub async fn open_mmap(
         self,
+        source: Option<&crate::config::GeoIpSource>,
     ) -> color_eyre::Result<maxminddb::Reader<maxminddb::Mmap>> {
-        let path = self.db_path().await?;
+        let path = if let Some(crate::config::GeoIpSource::Path(path)) = source
+        {
+            path.clone()
+        } else {
+            self.db_path().await?
+        };
         tokio::task::spawn_blocking(move || maxminddb::Reader::open_mmap(path))
             .await
             .wrap_err("failed to spawn tokio blocking task")?
@@ -216,3 +897,37 @@ impl DbType {
             })
     }
 }
+
+/// A proxy's exit IP resolved against the ASN/City readers, used for both
+/// `output`'s `by_country`/`by_asn` txt grouping and the TUI's live
+/// drill-down inspector. `None` fields mean either geolocation is disabled
+/// or the exit IP didn't resolve.
+#[derive(Default)]
+pub struct ProxyGeo {
+    pub country: Option<String>,
+    pub asn: Option<u32>,
+}
+
+pub fn resolve_geo(
+    exit_ip: Option<&str>,
+    maybe_asn_db: Option<&maxminddb::Reader<maxminddb::Mmap>>,
+    maybe_geo_db: Option<&maxminddb::Reader<maxminddb::Mmap>>,
+) -> crate::Result<ProxyGeo> {
+    let Some(exit_ip_addr) = exit_ip.map(str::parse::<IpAddr>).transpose()?
+    else {
+        return Ok(ProxyGeo::default());
+    };
+    let asn = maybe_asn_db
+        .map(|db| db.lookup::<maxminddb::geoip2::Asn<'_>>(exit_ip_addr))
+        .transpose()?
+        .flatten()
+        .and_then(|asn| asn.autonomous_system_number);
+    let country = maybe_geo_db
+        .map(|db| db.lookup::<maxminddb::geoip2::City<'_>>(exit_ip_addr))
+        .transpose()?
+        .flatten()
+        .and_then(|city| city.country)
+        .and_then(|country| country.iso_code)
+        .map(ToOwned::to_owned);
+    Ok(ProxyGeo { country, asn })
+}
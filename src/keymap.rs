@@ -0,0 +1,244 @@
+use std::path::Path;
+
+use color_eyre::eyre::{WrapErr as _, eyre};
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use crate::HashMap;
+
+/// A named TUI action a key chord can be bound to, mirroring the arms that
+/// used to be matched directly on [`KeyCode`] in `tui::handle_event`.
+#[derive(Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+pub enum Action {
+    ScrollLogsUp,
+    ScrollLogsDown,
+    /// Esc/q's two-stage behavior: stop the run if it's still in progress,
+    /// otherwise quit.
+    Stop,
+    Quit,
+    /// Suspends/resumes the scraping and checking workers without
+    /// cancelling the run.
+    TogglePause,
+
+    /// Toggles whether the `tui_logger` target selector pane has focus.
+    LogsToggleSelector,
+    /// Moves focus to the previous/next target in the selector pane
+    /// (distinct from [`Self::ScrollLogsUp`]/[`Self::ScrollLogsDown`],
+    /// which page through the log view itself).
+    LogsFocusUp,
+    LogsFocusDown,
+    /// Raises/lowers the log level shown for the selected target.
+    LogsRaiseLevel,
+    LogsLowerLevel,
+    /// Hides the selected target from the log view.
+    LogsHideTarget,
+    /// Isolates the log view to only the selected target.
+    LogsFocusTarget,
+
+    /// Switches between the gauge dashboard and the drill-down inspector
+    /// table listing individual working proxies.
+    ToggleInspector,
+    /// Cycles the inspector table's sort order (latency/country).
+    InspectorCycleSort,
+
+    /// Toggles the ticks-per-second diagnostics overlay.
+    ToggleFps,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct KeyChord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    /// Parses a chord string in the `<Modifier-...-Key>` style used by
+    /// dmm/dust-style TUI keymaps (e.g. `<Ctrl-c>`, `<Esc>`), or a bare
+    /// single character such as `k`.
+    fn parse(raw: &str) -> Option<Self> {
+        let inner = raw
+            .strip_prefix('<')
+            .and_then(|s| s.strip_suffix('>'))
+            .unwrap_or(raw);
+
+        let mut parts: Vec<&str> = inner.split('-').collect();
+        let key_part = parts.pop()?;
+
+        let mut modifiers = KeyModifiers::NONE;
+        for part in parts {
+            modifiers |= match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return None,
+            };
+        }
+
+        let code = match key_part.to_ascii_lowercase().as_str() {
+            "esc" | "escape" => KeyCode::Esc,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "space" => KeyCode::Char(' '),
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "enter" | "return" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            _ => {
+                let mut chars = key_part.chars();
+                let c = chars.next()?;
+                if chars.next().is_some() {
+                    return None;
+                }
+                KeyCode::Char(c)
+            }
+        };
+
+        Some(Self::new(code, modifiers))
+    }
+}
+
+const KEYMAP_ENV: &str = "PROXY_SCRAPER_CHECKER_KEYMAP";
+
+pub fn get_keymap_path() -> compact_str::CompactString {
+    std::env::var(KEYMAP_ENV).map_or_else(
+        move |_| compact_str::CompactString::const_new("keymap.ron"),
+        Into::into,
+    )
+}
+
+/// Key chord -> [`Action`] bindings driving `tui::handle_event`. Falls back
+/// to [`Self::default`]'s hardcoded bindings, which keep the TUI's previous
+/// scroll/stop/quit keys and add keys for the newer actions, whenever no
+/// keymap file is present.
+pub struct Keymap {
+    bindings: HashMap<KeyChord, Action>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut bindings = HashMap::default();
+        for raw in ["<Esc>", "q", "Q"] {
+            bindings.insert(KeyChord::parse(raw).unwrap(), Action::Stop);
+        }
+        bindings.insert(
+            KeyChord::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            Action::Quit,
+        );
+        bindings.insert(
+            KeyChord::new(KeyCode::Char('C'), KeyModifiers::CONTROL),
+            Action::Quit,
+        );
+        for raw in ["p", "P"] {
+            bindings.insert(KeyChord::parse(raw).unwrap(), Action::TogglePause);
+        }
+        for raw in ["<Up>", "<PageUp>", "k"] {
+            bindings
+                .insert(KeyChord::parse(raw).unwrap(), Action::ScrollLogsUp);
+        }
+        for raw in ["<Down>", "<PageDown>", "j"] {
+            bindings.insert(
+                KeyChord::parse(raw).unwrap(),
+                Action::ScrollLogsDown,
+            );
+        }
+
+        bindings.insert(
+            KeyChord::new(KeyCode::Char(' '), KeyModifiers::NONE),
+            Action::LogsToggleSelector,
+        );
+        bindings.insert(
+            KeyChord::new(KeyCode::Up, KeyModifiers::SHIFT),
+            Action::LogsFocusUp,
+        );
+        bindings.insert(
+            KeyChord::new(KeyCode::Down, KeyModifiers::SHIFT),
+            Action::LogsFocusDown,
+        );
+        bindings.insert(
+            KeyChord::new(KeyCode::Left, KeyModifiers::NONE),
+            Action::LogsLowerLevel,
+        );
+        bindings.insert(
+            KeyChord::new(KeyCode::Right, KeyModifiers::NONE),
+            Action::LogsRaiseLevel,
+        );
+        for c in ['+', '='] {
+            bindings.insert(
+                KeyChord::new(KeyCode::Char(c), KeyModifiers::NONE),
+                Action::LogsRaiseLevel,
+            );
+        }
+        bindings.insert(
+            KeyChord::new(KeyCode::Char('-'), KeyModifiers::NONE),
+            Action::LogsLowerLevel,
+        );
+        bindings.insert(
+            KeyChord::new(KeyCode::Char('h'), KeyModifiers::NONE),
+            Action::LogsHideTarget,
+        );
+        bindings.insert(
+            KeyChord::new(KeyCode::Char('f'), KeyModifiers::NONE),
+            Action::LogsFocusTarget,
+        );
+
+        bindings.insert(
+            KeyChord::new(KeyCode::Char('i'), KeyModifiers::NONE),
+            Action::ToggleInspector,
+        );
+        bindings.insert(
+            KeyChord::new(KeyCode::Char('s'), KeyModifiers::NONE),
+            Action::InspectorCycleSort,
+        );
+        bindings.insert(
+            KeyChord::new(KeyCode::Char('d'), KeyModifiers::NONE),
+            Action::ToggleFps,
+        );
+
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// Loads user overrides from a RON config table at `path`, merged on
+    /// top of [`Self::default`] so the file only needs to list the chords
+    /// it's rebinding. A missing file is not an error: it just means the
+    /// defaults apply unchanged.
+    pub async fn load(path: &Path) -> crate::Result<Self> {
+        let mut keymap = Self::default();
+
+        let text = match tokio::fs::read_to_string(path).await {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(keymap);
+            }
+            Err(e) => {
+                return Err(e).wrap_err_with(move || {
+                    format!("failed to read file to string: {}", path.display())
+                });
+            }
+        };
+
+        let overrides: HashMap<String, Action> =
+            ron::from_str(&text).wrap_err_with(move || {
+                format!("failed to parse RON keymap file: {}", path.display())
+            })?;
+        for (raw_chord, action) in overrides {
+            let chord = KeyChord::parse(&raw_chord).ok_or_else(|| {
+                eyre!("invalid key chord in keymap file: {raw_chord}")
+            })?;
+            keymap.bindings.insert(chord, action);
+        }
+
+        Ok(keymap)
+    }
+
+    pub fn action(&self, code: KeyCode, modifiers: KeyModifiers) -> Option<Action> {
+        self.bindings.get(&KeyChord::new(code, modifiers)).copied()
+    }
+}
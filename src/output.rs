@@ -1,7 +1,8 @@
 use std::{
     cmp::Ordering,
-    io,
+    io::{self, Write as _},
     net::{IpAddr, Ipv4Addr},
+    path::PathBuf,
     sync::Arc,
     time::Duration,
 };
@@ -12,11 +13,101 @@ use itertools::Itertools as _;
 use crate::{
     HashMap,
     config::Config,
-    ipdb,
-    proxy::{Proxy, ProxyType},
+    ipdb::{self, ProxyGeo, resolve_geo},
+    proxy::{Anonymity, Proxy, ProxyType},
     utils::is_docker,
 };
 
+#[derive(Clone, Copy, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    const fn extension(self) -> Option<&'static str> {
+        match self {
+            Self::None => None,
+            Self::Gzip => Some("gz"),
+            Self::Zstd => Some("zst"),
+        }
+    }
+
+    fn compress(self, data: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Self::None => Ok(data.to_vec()),
+            Self::Gzip => {
+                let mut encoder = flate2::write::GzEncoder::new(
+                    Vec::new(),
+                    flate2::Compression::default(),
+                );
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            Self::Zstd => zstd::encode_all(data, 0),
+        }
+    }
+}
+
+/// Path of the sidecar storing `path`'s current strong ETag, mirroring
+/// `ipdb::DbType::etag_path`'s `set_extension`-based sidecar naming.
+pub(crate) fn etag_path(path: &std::path::Path) -> PathBuf {
+    let mut etag_path = path.to_owned();
+    let new_extension = path.extension().map_or_else(
+        || "etag".to_owned(),
+        |current| format!("{}.etag", current.to_string_lossy()),
+    );
+    etag_path.set_extension(new_extension);
+    etag_path
+}
+
+/// The path `write_output` actually writes to once `compression` has had a
+/// chance to append its own extension, so callers that need to reach the
+/// same file later (e.g. [`crate::serve`]) don't have to reimplement the
+/// extension bookkeeping.
+pub(crate) fn compressed_path(
+    mut path: PathBuf,
+    compression: Compression,
+) -> PathBuf {
+    if let Some(extension) = compression.extension() {
+        let new_extension = path.extension().map_or_else(
+            || extension.to_owned(),
+            |current| format!("{}.{extension}", current.to_string_lossy()),
+        );
+        path.set_extension(new_extension);
+    }
+    path
+}
+
+async fn write_output(
+    path: PathBuf,
+    data: Vec<u8>,
+    compression: Compression,
+) -> crate::Result<()> {
+    let path = compressed_path(path, compression);
+    let data = if compression.extension().is_some() {
+        tokio::task::spawn_blocking(move || compression.compress(&data))
+            .await
+            .wrap_err("failed to spawn blocking compression task")??
+    } else {
+        data
+    };
+
+    let etag = format!("\"{}\"", blake3::hash(&data).to_hex());
+    let etag_path = etag_path(&path);
+
+    tokio::fs::write(&path, data).await.wrap_err_with({
+        let path = path.clone();
+        move || format!("failed to write to file: {}", path.display())
+    })?;
+    tokio::fs::write(&etag_path, etag).await.wrap_err_with(move || {
+        format!("failed to write to file: {}", etag_path.display())
+    })
+}
+
 fn compare_timeout(a: &Proxy, b: &Proxy) -> Ordering {
     a.timeout.unwrap_or(Duration::MAX).cmp(&b.timeout.unwrap_or(Duration::MAX))
 }
@@ -46,6 +137,7 @@ struct ProxyJson<'a> {
     exit_ip: Option<&'a str>,
     asn: Option<maxminddb::geoip2::Asn<'a>>,
     geolocation: Option<maxminddb::geoip2::City<'a>>,
+    anonymity: Option<Anonymity>,
 }
 
 fn group_proxies<'a>(
@@ -62,6 +154,77 @@ fn group_proxies<'a>(
     groups
 }
 
+/// Whether `geo` is allowed through `config.output.geo_filter`'s
+/// allow/block lists. A proxy with no allow-list match (including one
+/// whose exit IP didn't resolve, while an allow-list is set) is dropped;
+/// a proxy matching a block-list entry is dropped regardless of the
+/// allow-list.
+fn passes_geo_filter(config: &Config, geo: &ProxyGeo) -> bool {
+    let filter = &config.output.geo_filter;
+
+    if !filter.allowed_countries.is_empty() {
+        let matches = geo.country.as_deref().is_some_and(|country| {
+            filter
+                .allowed_countries
+                .iter()
+                .any(|c| c.eq_ignore_ascii_case(country))
+        });
+        if !matches {
+            return false;
+        }
+    }
+    if let Some(country) = geo.country.as_deref()
+        && filter
+            .blocked_countries
+            .iter()
+            .any(|c| c.eq_ignore_ascii_case(country))
+    {
+        return false;
+    }
+
+    if !filter.allowed_asns.is_empty() {
+        let matches = geo.asn.is_some_and(|asn| filter.allowed_asns.contains(&asn));
+        if !matches {
+            return false;
+        }
+    }
+    if let Some(asn) = geo.asn
+        && filter.blocked_asns.contains(&asn)
+    {
+        return false;
+    }
+
+    true
+}
+
+/// File name (before `.txt`) a proxy's `geo` lands in under `proxies/by_country`
+/// or `proxies/by_asn`. Proxies whose exit IP didn't resolve (or whose
+/// lookup found nothing) go into a shared `unknown` bucket.
+fn group_by_country<'a>(
+    proxies: &'a [Proxy],
+    geo: &[ProxyGeo],
+) -> HashMap<String, Vec<&'a Proxy>> {
+    let mut groups: HashMap<String, Vec<&Proxy>> = HashMap::default();
+    for (proxy, geo) in proxies.iter().zip(geo) {
+        let key = geo.country.clone().unwrap_or_else(|| "unknown".to_owned());
+        groups.entry(key).or_default().push(proxy);
+    }
+    groups
+}
+
+fn group_by_asn<'a>(
+    proxies: &'a [Proxy],
+    geo: &[ProxyGeo],
+) -> HashMap<String, Vec<&'a Proxy>> {
+    let mut groups: HashMap<String, Vec<&Proxy>> = HashMap::default();
+    for (proxy, geo) in proxies.iter().zip(geo) {
+        let key =
+            geo.asn.map_or_else(|| "unknown".to_owned(), |asn| asn.to_string());
+        groups.entry(key).or_default().push(proxy);
+    }
+    groups
+}
+
 #[expect(clippy::too_many_lines)]
 pub async fn save_proxies(
     config: Arc<Config>,
@@ -73,24 +236,53 @@ pub async fn save_proxies(
         proxies.sort_unstable_by(compare_natural);
     }
 
-    if config.output.json.enabled {
-        let (maybe_asn_db, maybe_geo_db) = tokio::try_join!(
-            async {
-                if config.output.json.include_asn {
-                    ipdb::DbType::Asn.open_mmap().await.map(Some)
-                } else {
-                    Ok(None)
-                }
-            },
-            async {
-                if config.output.json.include_geolocation {
-                    ipdb::DbType::Geo.open_mmap().await.map(Some)
-                } else {
-                    Ok(None)
-                }
+    let (maybe_asn_db, maybe_geo_db) = tokio::try_join!(
+        async {
+            if config.asn_enabled() {
+                ipdb::DbType::Asn
+                    .open_mmap(config.ipdb.asn_source.as_ref())
+                    .await
+                    .map(Some)
+            } else {
+                Ok(None)
             }
-        )?;
+        },
+        async {
+            if config.geolocation_enabled() {
+                ipdb::DbType::Geo
+                    .open_mmap(config.ipdb.geo_source.as_ref())
+                    .await
+                    .map(Some)
+            } else {
+                Ok(None)
+            }
+        }
+    )?;
+
+    let mut geo = Vec::with_capacity(proxies.len());
+    for proxy in &proxies {
+        geo.push(resolve_geo(
+            proxy.exit_ip.as_deref(),
+            maybe_asn_db.as_ref(),
+            maybe_geo_db.as_ref(),
+        )?);
+    }
+
+    let has_geo_filter = !config.output.geo_filter.allowed_countries.is_empty()
+        || !config.output.geo_filter.blocked_countries.is_empty()
+        || !config.output.geo_filter.allowed_asns.is_empty()
+        || !config.output.geo_filter.blocked_asns.is_empty();
+    if has_geo_filter {
+        let (kept_proxies, kept_geo) = proxies
+            .into_iter()
+            .zip(geo)
+            .filter(|(_, geo)| passes_geo_filter(&config, geo))
+            .unzip();
+        proxies = kept_proxies;
+        geo = kept_geo;
+    }
 
+    if config.output.json.enabled {
         let mut proxy_dicts = Vec::with_capacity(proxies.len());
         for proxy in &proxies {
             proxy_dicts.push(ProxyJson {
@@ -103,7 +295,9 @@ pub async fn save_proxies(
                     .timeout
                     .map(|d| (d.as_secs_f64() * 100.0).round() / 100.0_f64),
                 exit_ip: proxy.exit_ip.as_deref(),
-                asn: if let Some(asn_db) = &maybe_asn_db {
+                asn: if config.output.json.include_asn
+                    && let Some(asn_db) = &maybe_asn_db
+                {
                     if let Some(exit_ip) = proxy.exit_ip.as_ref() {
                         let exit_ip_addr: IpAddr = exit_ip.parse()?;
                         asn_db.lookup::<maxminddb::geoip2::Asn<'_>>(
@@ -115,7 +309,9 @@ pub async fn save_proxies(
                 } else {
                     None
                 },
-                geolocation: if let Some(geo_db) = &maybe_geo_db {
+                geolocation: if config.output.json.include_geolocation
+                    && let Some(geo_db) = &maybe_geo_db
+                {
                     if let Some(exit_ip) = proxy.exit_ip.as_ref() {
                         let exit_ip_addr: IpAddr = exit_ip.parse()?;
                         geo_db.lookup::<maxminddb::geoip2::City<'_>>(
@@ -127,6 +323,11 @@ pub async fn save_proxies(
                 } else {
                     None
                 },
+                anonymity: if config.output.json.include_anonymity {
+                    proxy.anonymity
+                } else {
+                    None
+                },
             });
         }
 
@@ -146,9 +347,8 @@ pub async fn save_proxies(
             } else {
                 serde_json::to_vec(&proxy_dicts)?
             };
-            tokio::fs::write(&path, json_data).await.wrap_err_with(
-                move || format!("failed to write to file: {}", path.display()),
-            )?;
+            write_output(path, json_data, config.output.json.compression)
+                .await?;
         }
     }
 
@@ -175,27 +375,82 @@ pub async fn save_proxies(
         )?;
 
         let text = create_proxy_list_str(proxies.iter(), true);
-        tokio::fs::write(directory_path.join("all.txt"), text)
-            .await
-            .wrap_err_with(|| {
-                format!(
-                    "failed to write to file: {}",
-                    directory_path.join("all.txt").display()
-                )
-            })?;
+        write_output(
+            directory_path.join("all.txt"),
+            text.into_bytes(),
+            config.output.txt.compression,
+        )
+        .await?;
 
         for (proto, proxies) in grouped_proxies {
             let text = create_proxy_list_str(proxies, false);
             let mut file_path = directory_path.join(proto.as_str());
             file_path.set_extension("txt");
-            tokio::fs::write(&file_path, text).await.wrap_err_with(
-                move || {
-                    format!("failed to write to file: {}", file_path.display())
+            write_output(file_path, text.into_bytes(), config.output.txt.compression)
+                .await?;
+        }
+
+        if config.output.txt.group_by_country {
+            let directory_path = directory_path.join("by_country");
+            tokio::fs::create_dir_all(&directory_path).await.wrap_err_with(
+                || {
+                    format!(
+                        "failed to create directory: {}",
+                        directory_path.display()
+                    )
                 },
             )?;
+            for (country, proxies) in group_by_country(&proxies, &geo) {
+                let text = create_proxy_list_str(proxies, false);
+                let mut file_path = directory_path.join(&country);
+                file_path.set_extension("txt");
+                write_output(
+                    file_path,
+                    text.into_bytes(),
+                    config.output.txt.compression,
+                )
+                .await?;
+            }
+        }
+
+        if config.output.txt.group_by_asn {
+            let directory_path = directory_path.join("by_asn");
+            tokio::fs::create_dir_all(&directory_path).await.wrap_err_with(
+                || {
+                    format!(
+                        "failed to create directory: {}",
+                        directory_path.display()
+                    )
+                },
+            )?;
+            for (asn, proxies) in group_by_asn(&proxies, &geo) {
+                let text = create_proxy_list_str(proxies, false);
+                let mut file_path = directory_path.join(&asn);
+                file_path.set_extension("txt");
+                write_output(
+                    file_path,
+                    text.into_bytes(),
+                    config.output.txt.compression,
+                )
+                .await?;
+            }
         }
     }
 
+    // Structured per-save counts. Re-running this pipeline on an interval
+    // and swapping the `Arc<Config>` on config file changes are handled by
+    // `run_loop` in `main.rs` and `watch::watch_and_reload`, not here.
+    let by_protocol = group_proxies(&config, &proxies)
+        .iter()
+        .map(|(protocol, proxies)| format!("{}={}", protocol.as_str(), proxies.len()))
+        .sorted()
+        .join(", ");
+    tracing::info!(
+        total = proxies.len(),
+        by_protocol = %by_protocol,
+        "Saved proxies"
+    );
+
     let path = config
         .output
         .path
@@ -209,6 +464,12 @@ pub async fn save_proxies(
     } else {
         tracing::info!("Proxies have been saved to {}", path.display());
     }
+    crate::systemd::notify_status(&format!(
+        "saved {} working proxies to {}",
+        proxies.len(),
+        path.display()
+    ));
+    crate::systemd::notify_ready();
 
     Ok(())
 }
@@ -1,9 +1,12 @@
 use std::sync::LazyLock;
 
+use color_eyre::eyre::{OptionExt as _, WrapErr as _};
 use ipnetwork::IpNetwork;
 
+use crate::proxy::{Proxy, ProxyType};
+
 pub static PROXY_REGEX: LazyLock<fancy_regex::Regex> = LazyLock::new(|| {
-    let pattern = r"(?:^|[^0-9A-Za-z])(?:(?P<protocol>https?|socks[45]):\/\/)?(?:(?P<username>[0-9A-Za-z]{1,64}):(?P<password>[0-9A-Za-z]{1,64})@)?(?P<host>[A-Za-z][\-\.A-Za-z]{0,251}[A-Za-z]|[A-Za-z]|(?:[0-9]|[1-9][0-9]|1[0-9]{2}|2[0-4][0-9]|25[0-5])(?:\.(?:[0-9]|[1-9][0-9]|1[0-9]{2}|2[0-4][0-9]|25[0-5])){3}):(?P<port>[0-9]|[1-9][0-9]{1,3}|[1-5][0-9]{4}|6[0-4][0-9]{3}|65[0-4][0-9]{2}|655[0-2][0-9]|6553[0-5])(?=[^0-9A-Za-z]|$)";
+    let pattern = r"(?:^|[^0-9A-Za-z])(?:(?P<protocol>https?|socks5h|socks[45]):\/\/)?(?:(?P<username>[0-9A-Za-z]{1,64}):(?P<password>[0-9A-Za-z]{1,64})@)?(?P<host>[A-Za-z][\-\.A-Za-z]{0,251}[A-Za-z]|[A-Za-z]|(?:[0-9]|[1-9][0-9]|1[0-9]{2}|2[0-4][0-9]|25[0-5])(?:\.(?:[0-9]|[1-9][0-9]|1[0-9]{2}|2[0-4][0-9]|25[0-5])){3}):(?P<port>[0-9]|[1-9][0-9]{1,3}|[1-5][0-9]{4}|6[0-4][0-9]{3}|65[0-4][0-9]{2}|655[0-2][0-9]|6553[0-5])(?=[^0-9A-Za-z]|$)";
     fancy_regex::RegexBuilder::new(pattern)
         .backtrack_limit(usize::MAX)
         .build()
@@ -20,6 +23,152 @@ static CIDR_REGEX: LazyLock<fancy_regex::Regex> = LazyLock::new(|| {
     fancy_regex::Regex::new(pattern).unwrap()
 });
 
+static CIDR_V6_REGEX: LazyLock<fancy_regex::Regex> = LazyLock::new(|| {
+    let pattern = r"(?:^|[^0-9A-Za-z])\[(?P<network>[0-9A-Fa-f:]+)\]/(?P<prefix>[0-9]|[1-9][0-9]|1[01][0-9]|12[0-8]):(?P<port>[0-9]|[1-9][0-9]{1,3}|[1-5][0-9]{4}|6[0-4][0-9]{3}|65[0-4][0-9]{2}|655[0-2][0-9]|6553[0-5])(?=[^0-9A-Za-z]|$)";
+    fancy_regex::Regex::new(pattern).unwrap()
+});
+
+/// The shape a source's body is parsed as. `Text` is the historical
+/// free-text/regex path; `Json`/`Csv` let structured, API-style sources be
+/// read directly instead of silently yielding "no proxies found".
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SourceFormat {
+    Json,
+    Csv,
+    Text,
+}
+
+impl SourceFormat {
+    /// Picks a format from the HTTP `Content-Type` header when present,
+    /// otherwise guesses from `source_url`'s file extension (for
+    /// `file://`/local-path sources).
+    pub fn detect(content_type: Option<&str>, source_url: &str) -> Self {
+        let guessed = content_type.map(ToOwned::to_owned).or_else(|| {
+            mime_guess::from_path(source_url)
+                .first_raw()
+                .map(ToOwned::to_owned)
+        });
+        let essence = guessed
+            .as_deref()
+            .and_then(|mime| mime.split(';').next())
+            .map(str::trim);
+        match essence {
+            Some(mime) if mime.eq_ignore_ascii_case("application/json") => {
+                Self::Json
+            }
+            Some(mime) if mime.eq_ignore_ascii_case("text/csv") => Self::Csv,
+            _ => Self::Text,
+        }
+    }
+}
+
+/// A single proxy record as published by structured (JSON/CSV) sources.
+#[derive(serde::Deserialize)]
+struct ProxyRecord {
+    host: String,
+    port: u16,
+    #[serde(default)]
+    protocol: Option<String>,
+    #[serde(default)]
+    username: Option<String>,
+    #[serde(default)]
+    password: Option<String>,
+}
+
+fn record_to_proxy(
+    record: ProxyRecord,
+    default_protocol: ProxyType,
+    custom_scheme: Option<&compact_str::CompactString>,
+) -> crate::Result<Proxy> {
+    let protocol = match record.protocol {
+        Some(protocol) => protocol.parse()?,
+        None => default_protocol,
+    };
+    Ok(Proxy {
+        protocol,
+        host: record.host.into(),
+        port: record.port,
+        username: record.username.map(Into::into),
+        password: record.password.map(Into::into),
+        timeout: None,
+        exit_ip: None,
+        anonymity: None,
+        proxy_protocol_supported: None,
+        custom_scheme: custom_scheme.cloned(),
+    })
+}
+
+/// Deserializes a JSON array of `{host, port, protocol?, username?,
+/// password?}` records into [`Proxy`]s.
+pub fn parse_json_proxies(
+    text: &str,
+    default_protocol: ProxyType,
+    custom_scheme: Option<&compact_str::CompactString>,
+) -> crate::Result<Vec<Proxy>> {
+    let records: Vec<ProxyRecord> = serde_json::from_str(text)
+        .wrap_err("failed to parse source as JSON")?;
+    records
+        .into_iter()
+        .map(|record| record_to_proxy(record, default_protocol, custom_scheme))
+        .collect()
+}
+
+/// Parses a CSV document with a `host,port,protocol,username,password`
+/// header (the latter three columns optional) into [`Proxy`]s.
+pub fn parse_csv_proxies(
+    text: &str,
+    default_protocol: ProxyType,
+    custom_scheme: Option<&compact_str::CompactString>,
+) -> crate::Result<Vec<Proxy>> {
+    let mut reader = csv::Reader::from_reader(text.as_bytes());
+    reader
+        .deserialize::<ProxyRecord>()
+        .map(|result| {
+            let record =
+                result.wrap_err("failed to parse source as CSV")?;
+            record_to_proxy(record, default_protocol, custom_scheme)
+        })
+        .collect()
+}
+
+/// Runs [`PROXY_REGEX`] over free-text source bodies - the historical
+/// parsing path, kept as the fallback for sources that aren't JSON or CSV.
+pub fn parse_text_proxies(
+    text: &str,
+    default_protocol: ProxyType,
+    custom_scheme: Option<&compact_str::CompactString>,
+) -> crate::Result<Vec<Proxy>> {
+    let mut proxies = Vec::new();
+    for maybe_capture in PROXY_REGEX.captures_iter(text) {
+        let capture = maybe_capture?;
+        let protocol = match capture.name("protocol") {
+            Some(m) => m.as_str().parse()?,
+            None => default_protocol,
+        };
+        proxies.push(Proxy {
+            protocol,
+            host: capture
+                .name("host")
+                .ok_or_eyre("failed to match \"host\" regex capture group")?
+                .as_str()
+                .into(),
+            port: capture
+                .name("port")
+                .ok_or_eyre("failed to match \"port\" regex capture group")?
+                .as_str()
+                .parse()?,
+            username: capture.name("username").map(|m| m.as_str().into()),
+            password: capture.name("password").map(|m| m.as_str().into()),
+            timeout: None,
+            exit_ip: None,
+            anonymity: None,
+            proxy_protocol_supported: None,
+            custom_scheme: custom_scheme.cloned(),
+        });
+    }
+    Ok(proxies)
+}
+
 pub fn parse_ipv4(s: &str) -> Option<String> {
     if let Ok(Some(captures)) = IPV4_REGEX.captures(s) {
         captures.name("host").map(|capture| capture.as_str().to_owned())
@@ -28,86 +177,106 @@ pub fn parse_ipv4(s: &str) -> Option<String> {
     }
 }
 
-/// Expands CIDR ranges in text into individual IP:port entries
-/// Supports format like "192.168.1.0/24:8080" which expands to all IPs in the range
-/// Handles various separators (spaces, commas, newlines, etc.) between entries
-pub fn expand_cidr_ranges(text: &str) -> String {
-    let mut result = text.to_string();
-    let mut offset: i32 = 0;
-    
-    // Find all CIDR matches and expand them
-    let captures: Vec<_> = CIDR_REGEX.captures_iter(text)
-        .filter_map(|m| m.ok())
-        .collect();
-    
-    for capture in captures {
-        if let (Some(network), Some(prefix), Some(port)) = (
-            capture.name("network"), 
-            capture.name("prefix"), 
-            capture.name("port")
-        ) {
-            let cidr_str = format!("{}/{}", network.as_str(), prefix.as_str());
-            
-            match cidr_str.parse::<IpNetwork>() {
-                Ok(network) => {
-                    // Generate expanded IPs
-                    let expanded_ips: Vec<String> = network.iter()
-                        .filter(|ip| ip.is_ipv4())
-                        .map(|ip| format!("{}:{}", ip, port.as_str()))
-                        .collect();
-                    
-                    if !expanded_ips.is_empty() {
-                        // Get the full match including any leading non-alphanumeric character
-                        let full_match = capture.get(0).unwrap();
-                        let match_start = (full_match.start() as i32 + offset) as usize;
-                        let match_end = (full_match.end() as i32 + offset) as usize;
-                        
-                        // Determine what separator to use by checking what follows
-                        let separator = if match_end < result.len() {
-                            let next_char = result.chars().nth(match_end);
-                            match next_char {
-                                Some('\n') => "\n",
-                                Some('\t') => "\t", 
-                                Some(',') => ",",
-                                _ => " ",
-                            }
-                        } else {
-                            "\n"
-                        };
-                        
-                        // Join expanded IPs with the detected separator
-                        let replacement = expanded_ips.join(separator);
-                        
-                        // Handle case where match starts with a delimiter character
-                        let (_actual_start, prefix_char) = if match_start > 0 {
-                            let prev_char = result.chars().nth(match_start);
-                            if prev_char.map_or(false, |c| !c.is_ascii_alphanumeric()) {
-                                (match_start + 1, result.chars().nth(match_start).unwrap().to_string())
-                            } else {
-                                (match_start, String::new())
-                            }
-                        } else {
-                            (match_start, String::new())
-                        };
-                        
-                        let final_replacement = format!("{}{}", prefix_char, replacement);
-                        
-                        // Replace the CIDR pattern with expanded IPs
-                        result.replace_range(match_start..match_end, &final_replacement);
-                        
-                        // Update offset for subsequent replacements
-                        let len_diff = final_replacement.len() as i32 - (match_end - match_start) as i32;
-                        offset += len_diff;
-                    }
-                }
-                Err(_) => {
-                    // If parsing fails, leave the original text unchanged
-                    continue;
+/// Default cap on how many hosts a single CIDR range in
+/// [`expand_cidr_ranges`] is allowed to expand to.
+pub const DEFAULT_MAX_CIDR_HOSTS: u64 = 65_536;
+
+/// Number of addresses in `network`, computed from its prefix length
+/// instead of iterating, so [`expand_cidr_ranges`] can reject an
+/// oversized range before allocating anything. Saturates at `u64::MAX`
+/// for IPv6 ranges wide enough to overflow it.
+fn host_count(network: &IpNetwork) -> u64 {
+    let addr_bits: u32 = if network.is_ipv4() { 32 } else { 128 };
+    let host_bits = addr_bits - u32::from(network.prefix());
+    if host_bits >= 64 { u64::MAX } else { 1_u64 << host_bits }
+}
+
+struct CidrMatch<'t> {
+    start: usize,
+    end: usize,
+    cidr: String,
+    port: &'t str,
+}
+
+/// Collects non-overlapping `network/prefix:port` matches from `regex`,
+/// spanning from the start of the `network` capture to the end of the
+/// `port` capture so the pattern's leading delimiter character (consumed
+/// by the `(?:^|[^0-9A-Za-z])` alternation but not part of the CIDR
+/// itself) is never touched by the caller.
+fn collect_cidr_matches<'t>(
+    regex: &fancy_regex::Regex,
+    text: &'t str,
+) -> Vec<CidrMatch<'t>> {
+    regex
+        .captures_iter(text)
+        .filter_map(Result::ok)
+        .filter_map(|capture| {
+            let network = capture.name("network")?;
+            let prefix = capture.name("prefix")?.as_str();
+            let port = capture.name("port")?;
+            Some(CidrMatch {
+                start: network.start(),
+                end: port.end(),
+                cidr: format!("{}/{prefix}", network.as_str()),
+                port: port.as_str(),
+            })
+        })
+        .collect()
+}
+
+/// Expands CIDR ranges (`network/prefix:port` for IPv4,
+/// `[network]/prefix:port` for IPv6) found in `text` into individual
+/// `host:port` entries, leaving everything else - including separators
+/// between entries - untouched.
+///
+/// Matches are streamed directly into the returned `String` in a single
+/// pass instead of rewriting `text` in place, so there's no offset
+/// bookkeeping. A range whose host count (checked cheaply via
+/// [`host_count`], before any allocation) exceeds `max_cidr_hosts` is
+/// logged and left unexpanded, and expanded entries are deduplicated
+/// across the whole input so overlapping ranges don't produce repeats.
+pub fn expand_cidr_ranges(text: &str, max_cidr_hosts: u64) -> String {
+    let mut matches = collect_cidr_matches(&CIDR_REGEX, text);
+    matches.extend(collect_cidr_matches(&CIDR_V6_REGEX, text));
+    matches.sort_by_key(|m| m.start);
+
+    let mut result = String::with_capacity(text.len());
+    let mut seen: crate::HashSet<String> = crate::HashSet::default();
+    let mut cursor = 0;
+    for m in &matches {
+        if m.start < cursor {
+            // Overlapped with a match already consumed above; skip it
+            // rather than risk slicing into the middle of a UTF-8
+            // boundary we've already copied past.
+            continue;
+        }
+        let Ok(network) = m.cidr.parse::<IpNetwork>() else {
+            continue;
+        };
+        if host_count(&network) > max_cidr_hosts {
+            tracing::warn!(
+                "CIDR range {} expands to more than {max_cidr_hosts} \
+                 hosts; leaving it unexpanded",
+                m.cidr
+            );
+            continue;
+        }
+
+        result.push_str(&text[cursor..m.start]);
+        let mut first = true;
+        for ip in network.iter() {
+            let entry = format!("{ip}:{}", m.port);
+            if seen.insert(entry.clone()) {
+                if !first {
+                    result.push('\n');
                 }
+                result.push_str(&entry);
+                first = false;
             }
         }
+        cursor = m.end;
     }
-    
+    result.push_str(&text[cursor..]);
     result
 }
 
@@ -119,7 +288,7 @@ mod tests {
     fn test_cidr_expansion() {
         // Test basic CIDR expansion
         let input = "192.168.1.0/30:8080";
-        let result = expand_cidr_ranges(input);
+        let result = expand_cidr_ranges(input, DEFAULT_MAX_CIDR_HOSTS);
         let lines: Vec<&str> = result.trim().split('\n').collect();
         
         assert_eq!(lines.len(), 4);
@@ -132,7 +301,7 @@ mod tests {
     #[test]
     fn test_mixed_input() {
         let input = "192.168.1.0/31:8080\n127.0.0.1:9090\ninvalid-line";
-        let result = expand_cidr_ranges(input);
+        let result = expand_cidr_ranges(input, DEFAULT_MAX_CIDR_HOSTS);
         let lines: Vec<&str> = result.trim().split('\n').collect();
         
         // Should have 2 CIDR-expanded IPs + 1 regular IP + 1 invalid line
@@ -146,7 +315,7 @@ mod tests {
     #[test]
     fn test_single_ip_cidr() {
         let input = "10.0.0.1/32:3128";
-        let result = expand_cidr_ranges(input);
+        let result = expand_cidr_ranges(input, DEFAULT_MAX_CIDR_HOSTS);
         assert_eq!(result.trim(), "10.0.0.1:3128");
     }
 
@@ -154,7 +323,7 @@ mod tests {
     fn test_non_newline_separated_behavior() {
         // Test space-separated entries with CIDR expansion
         let input = "192.168.1.0/31:8080 127.0.0.1:9090";
-        let result = expand_cidr_ranges(input);
+        let result = expand_cidr_ranges(input, DEFAULT_MAX_CIDR_HOSTS);
         
         // Should expand the CIDR range and preserve the regular proxy
         assert!(result.contains("192.168.1.0:8080"));
@@ -166,7 +335,7 @@ mod tests {
     fn test_multiple_cidr_same_line_behavior() {
         // Test multiple CIDR ranges on same line
         let input = "192.168.1.0/31:8080 10.0.0.0/31:3128";
-        let result = expand_cidr_ranges(input);
+        let result = expand_cidr_ranges(input, DEFAULT_MAX_CIDR_HOSTS);
         
         // Should expand both CIDR ranges
         assert!(result.contains("192.168.1.0:8080"));
@@ -178,7 +347,7 @@ mod tests {
     #[test]
     fn test_comma_separated_cidr() {
         let input = "192.168.1.0/31:8080,10.0.0.0/31:3128";
-        let result = expand_cidr_ranges(input);
+        let result = expand_cidr_ranges(input, DEFAULT_MAX_CIDR_HOSTS);
         
         // Should expand both CIDR ranges and preserve comma separation
         assert!(result.contains("192.168.1.0:8080"));
@@ -190,7 +359,7 @@ mod tests {
     #[test]
     fn test_mixed_separators() {
         let input = "192.168.1.0/31:8080\t10.0.0.1:3128,203.0.113.0/31:1080 127.0.0.1:9090";
-        let result = expand_cidr_ranges(input);
+        let result = expand_cidr_ranges(input, DEFAULT_MAX_CIDR_HOSTS);
         
         // Should expand CIDR ranges and preserve non-CIDR entries
         assert!(result.contains("192.168.1.0:8080"));
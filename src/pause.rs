@@ -0,0 +1,53 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+/// Shared runtime pause switch polled by the scraping and checking worker
+/// loops between requests, so a TUI hotkey can suspend issuing new work
+/// without cancelling the run (see [`crate::tui::AppMode::Paused`]).
+#[derive(Clone)]
+pub struct PauseControl {
+    paused: Arc<AtomicBool>,
+    notify: Arc<tokio::sync::Notify>,
+}
+
+impl Default for PauseControl {
+    fn default() -> Self {
+        Self {
+            paused: Arc::new(AtomicBool::new(false)),
+            notify: Arc::new(tokio::sync::Notify::new()),
+        }
+    }
+}
+
+impl PauseControl {
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    pub fn toggle(&self) {
+        let paused = !self.paused.load(Ordering::Relaxed);
+        self.paused.store(paused, Ordering::Relaxed);
+        if !paused {
+            self.notify.notify_waiters();
+        }
+    }
+
+    /// Suspends the caller for as long as the control is paused. Workers
+    /// call this once per iteration, right before issuing their next
+    /// request.
+    pub async fn wait_if_paused(&self) {
+        loop {
+            // Register for notification before re-checking `is_paused`, or a
+            // `toggle()` landing between the check and the `notified()` call
+            // would call `notify_waiters()` before we're listening, and the
+            // wakeup would be lost for good - `Notify` has no durable permit.
+            let notified = self.notify.notified();
+            if !self.is_paused() {
+                break;
+            }
+            notified.await;
+        }
+    }
+}
@@ -8,12 +8,51 @@ use std::{
 use color_eyre::eyre::eyre;
 
 use crate::{
+    HashMap,
     config::{Config, HttpbinResponse},
     parsers::parse_ipv4,
 };
 
+static IDENTITY_HEADERS: [&str; 5] =
+    ["via", "x-forwarded-for", "x-real-ip", "forwarded", "proxy-connection"];
+
+#[derive(Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Anonymity {
+    Transparent,
+    Anonymous,
+    Elite,
+}
+
+impl Anonymity {
+    fn classify(
+        real_ip: &str,
+        exit_ip: Option<&str>,
+        headers: Option<&HashMap<String, String>>,
+    ) -> Self {
+        if exit_ip.is_some_and(|ip| ip == real_ip) {
+            return Self::Transparent;
+        }
+
+        let real_ip_leaked = headers.is_some_and(|headers| {
+            headers.values().any(|value| value.contains(real_ip))
+        });
+        if real_ip_leaked {
+            return Self::Transparent;
+        }
+
+        let leaks_identity = headers.is_some_and(|headers| {
+            headers
+                .keys()
+                .any(|name| IDENTITY_HEADERS.contains(&name.to_ascii_lowercase().as_str()))
+        });
+
+        if leaks_identity { Self::Anonymous } else { Self::Elite }
+    }
+}
+
 #[derive(
-    Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize,
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize,
 )]
 #[cfg_attr(feature = "tui", derive(strum::EnumCount))]
 #[serde(rename_all = "lowercase")]
@@ -31,7 +70,9 @@ impl FromStr for ProxyType {
             Ok(Self::Http)
         } else if s.eq_ignore_ascii_case("socks4") {
             Ok(Self::Socks4)
-        } else if s.eq_ignore_ascii_case("socks5") {
+        } else if s.eq_ignore_ascii_case("socks5")
+            || s.eq_ignore_ascii_case("socks5h")
+        {
             Ok(Self::Socks5)
         } else {
             Err(eyre!("failed to convert {s} to ProxyType"))
@@ -49,7 +90,7 @@ impl ProxyType {
     }
 }
 
-#[derive(Eq)]
+#[derive(Clone, Eq)]
 pub struct Proxy {
     pub protocol: ProxyType,
     pub host: compact_str::CompactString,
@@ -58,6 +99,15 @@ pub struct Proxy {
     pub password: Option<compact_str::CompactString>,
     pub timeout: Option<Duration>,
     pub exit_ip: Option<compact_str::CompactString>,
+    pub anonymity: Option<Anonymity>,
+    /// Whether this proxy was observed to honour a PROXY protocol v2 header
+    /// injected ahead of the tunnelled request by
+    /// [`crate::proxy_protocol::probe`]. `None` means it wasn't probed
+    /// (`checking.probe_proxy_protocol` is off, there's no `check_url`, the
+    /// proxy is SOCKS4, or the proxy uses a custom connector the probe can't
+    /// dial directly) or that the probe itself failed.
+    pub proxy_protocol_supported: Option<bool>,
+    pub custom_scheme: Option<compact_str::CompactString>,
 }
 
 impl TryFrom<&mut Proxy> for reqwest::Proxy {
@@ -116,11 +166,16 @@ impl Proxy {
         &mut self,
         config: &Config,
         dns_resolver: Arc<R>,
+        real_ip: Option<&str>,
     ) -> crate::Result<()> {
         if let Some(check_url) = config.checking.check_url.clone() {
-            let builder = reqwest::ClientBuilder::new()
+            let custom_connector = self
+                .custom_scheme
+                .as_deref()
+                .and_then(|scheme| config.custom_connectors.get(scheme));
+
+            let mut builder = reqwest::ClientBuilder::new()
                 .user_agent(config.checking.user_agent.as_bytes())
-                .proxy(self.try_into()?)
                 .timeout(config.checking.timeout)
                 .connect_timeout(config.checking.connect_timeout)
                 .pool_idle_timeout(Duration::ZERO)
@@ -130,28 +185,75 @@ impl Proxy {
                 .tcp_keepalive_interval(Duration::ZERO)
                 .tcp_keepalive_retries(0)
                 .dns_resolver(dns_resolver);
+
+            builder = if let Some(connector) = custom_connector {
+                builder.connector_layer(
+                    crate::connector::CustomConnectorLayer::new(
+                        Arc::clone(connector),
+                        self.host.clone(),
+                        self.port,
+                    ),
+                )
+            } else {
+                let mut builder = builder.proxy(self.try_into()?);
+                if let Some(upstream_proxy) = &config.upstream_proxy {
+                    builder = builder.connector_layer(
+                        crate::socks::UpstreamSocksLayer::new(Arc::clone(
+                            upstream_proxy,
+                        )),
+                    );
+                }
+                builder
+            };
             #[cfg(any(
                 target_os = "android",
                 target_os = "fuchsia",
                 target_os = "linux"
             ))]
             let builder = builder.tcp_user_timeout(None);
+            let builder = if check_url.scheme() == "https"
+                && !config.checking.spki_pins.is_empty()
+            {
+                builder.use_preconfigured_tls(
+                    crate::tls::build_pinned_client_config(
+                        config.checking.spki_pins.clone(),
+                    )?,
+                )
+            } else {
+                builder
+            };
             let request = {
                 let client = builder.build()?;
-                client.get(check_url)
+                client.get(check_url.clone())
             };
             let start = Instant::now();
             let response = request.send().await?.error_for_status()?;
             self.timeout = Some(start.elapsed());
-            self.exit_ip = response.text().await.map_or(None, |text| {
-                if let Ok(httpbin) =
-                    serde_json::from_str::<HttpbinResponse>(&text)
-                {
-                    parse_ipv4(&httpbin.origin)
-                } else {
-                    parse_ipv4(&text)
-                }
+
+            let text = response.text().await.ok();
+            let httpbin = text
+                .as_deref()
+                .and_then(|text| serde_json::from_str::<HttpbinResponse>(text).ok());
+            self.exit_ip = httpbin
+                .as_ref()
+                .and_then(|httpbin| parse_ipv4(&httpbin.origin))
+                .or_else(|| text.as_deref().and_then(parse_ipv4));
+            self.anonymity = real_ip.map(|real_ip| {
+                Anonymity::classify(
+                    real_ip,
+                    self.exit_ip.as_deref(),
+                    httpbin.as_ref().map(|httpbin| &httpbin.headers),
+                )
             });
+
+            self.proxy_protocol_supported = if config.checking.probe_proxy_protocol
+                && custom_connector.is_none()
+                && self.protocol != ProxyType::Socks4
+            {
+                crate::proxy_protocol::probe(self, &check_url).await.ok()
+            } else {
+                None
+            };
         }
         Ok(())
     }
@@ -0,0 +1,120 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use color_eyre::eyre::{OptionExt as _, eyre};
+use tokio::io::{AsyncBufReadExt as _, AsyncReadExt as _, AsyncWriteExt as _};
+
+use crate::{balancer, config::HttpbinResponse, proxy::Proxy};
+
+const SIGNATURE: [u8; 12] = [
+    0x0D, 0x0A, 0x0D, 0x0A, 0x00, 0x0D, 0x0A, 0x51, 0x55, 0x49, 0x54, 0x0A,
+];
+
+/// The client address [`probe`] advertises over PROXY protocol v2, drawn
+/// from the IPv4 documentation range (RFC 5737 TEST-NET-1) so it can never
+/// collide with a real client and is unambiguous to spot in a response.
+const ADVERTISED_CLIENT_V4: SocketAddr =
+    SocketAddr::new(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)), 12345);
+/// IPv6 equivalent, drawn from the documentation prefix (RFC 3849).
+const ADVERTISED_CLIENT_V6: SocketAddr = SocketAddr::new(
+    IpAddr::V6(Ipv6Addr::new(0x2001, 0x0DB8, 0, 0, 0, 0, 0, 1)),
+    12345,
+);
+
+/// Encodes a binary PROXY protocol v2 header advertising `client` as the
+/// original source of a connection tunnelled to `destination`. See
+/// <https://www.haproxy.org/download/2.8/doc/proxy-protocol.txt>.
+fn encode_v2(client: SocketAddr, destination: SocketAddr) -> Vec<u8> {
+    let mut header = SIGNATURE.to_vec();
+    header.push(0x21); // version 2, PROXY command
+
+    match (client, destination) {
+        (SocketAddr::V4(src), SocketAddr::V4(dst)) => {
+            header.push(0x11); // AF_INET, SOCK_STREAM
+            header.extend_from_slice(&12_u16.to_be_bytes());
+            header.extend_from_slice(&src.ip().octets());
+            header.extend_from_slice(&dst.ip().octets());
+            header.extend_from_slice(&src.port().to_be_bytes());
+            header.extend_from_slice(&dst.port().to_be_bytes());
+        }
+        (SocketAddr::V6(src), SocketAddr::V6(dst)) => {
+            header.push(0x21); // AF_INET6, SOCK_STREAM
+            header.extend_from_slice(&36_u16.to_be_bytes());
+            header.extend_from_slice(&src.ip().octets());
+            header.extend_from_slice(&dst.ip().octets());
+            header.extend_from_slice(&src.port().to_be_bytes());
+            header.extend_from_slice(&dst.port().to_be_bytes());
+        }
+        _ => unreachable!("client and destination are picked the same family"),
+    }
+
+    header
+}
+
+/// Tunnels to `check_url` through `proxy` behind a PROXY protocol v2 header
+/// advertising a synthetic client address, then checks whether the response
+/// actually reflects that address back - meaning `proxy` (or whatever it's
+/// chained to) parses and honours the header rather than just tolerating
+/// the extra bytes ahead of the HTTP request.
+pub async fn probe(proxy: &Proxy, check_url: &url::Url) -> crate::Result<bool> {
+    let dst_host = check_url.host_str().ok_or_eyre("check_url has no host")?;
+    let dst_port = check_url
+        .port_or_known_default()
+        .ok_or_eyre("check_url has no known port")?;
+
+    let destination_ip = tokio::net::lookup_host((dst_host, dst_port))
+        .await?
+        .next()
+        .ok_or_eyre("failed to resolve check_url host")?
+        .ip();
+    let (advertised_client, destination) = match destination_ip {
+        IpAddr::V4(ip) => {
+            (ADVERTISED_CLIENT_V4, SocketAddr::new(IpAddr::V4(ip), dst_port))
+        }
+        IpAddr::V6(ip) => {
+            (ADVERTISED_CLIENT_V6, SocketAddr::new(IpAddr::V6(ip), dst_port))
+        }
+    };
+
+    let mut stream = balancer::dial_upstream(proxy, dst_host, dst_port).await?;
+    stream.write_all(&encode_v2(advertised_client, destination)).await?;
+
+    let path = check_url.query().map_or_else(
+        || check_url.path().to_owned(),
+        |query| format!("{}?{query}", check_url.path()),
+    );
+    stream
+        .write_all(
+            format!(
+                "GET {path} HTTP/1.1\r\nHost: {dst_host}\r\nConnection: \
+                 close\r\n\r\n"
+            )
+            .as_bytes(),
+        )
+        .await?;
+
+    let mut reader = tokio::io::BufReader::new(stream);
+    let mut status_line = String::new();
+    reader.read_line(&mut status_line).await?;
+    if !status_line.contains(" 200 ") {
+        return Err(eyre!(
+            "check_url rejected the probed request: {}",
+            status_line.trim()
+        ));
+    }
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).await?;
+        if line.is_empty() || line == "\r\n" {
+            break;
+        }
+    }
+
+    let mut body = String::new();
+    reader.read_to_string(&mut body).await.ok();
+
+    Ok(serde_json::from_str::<HttpbinResponse>(&body)
+        .ok()
+        .is_some_and(|httpbin| {
+            httpbin.origin.contains(&advertised_client.ip().to_string())
+        }))
+}
@@ -1,5 +1,6 @@
 use std::{
     env,
+    net::IpAddr,
     num::NonZero,
     path::{Path, PathBuf},
 };
@@ -7,7 +8,12 @@ use std::{
 use color_eyre::eyre::WrapErr as _;
 use serde::Deserialize as _;
 
-use crate::{HashMap, http::BasicAuth};
+use crate::{
+    HashMap,
+    http::{BasicAuth, SourceAuth},
+    output::Compression,
+    tls::TlsConfig,
+};
 
 fn validate_positive_f64<'de, D: serde::Deserializer<'de>>(
     deserializer: D,
@@ -65,6 +71,78 @@ where
     }
 }
 
+fn validate_non_negative_f64<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<f64, D::Error> {
+    let val = f64::deserialize(deserializer)?;
+    if val >= 0.0 {
+        Ok(val)
+    } else {
+        Err(serde::de::Error::custom("value must not be negative"))
+    }
+}
+
+fn validate_unit_interval<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<f64, D::Error> {
+    let val = f64::deserialize(deserializer)?;
+    if (0.0..=1.0).contains(&val) {
+        Ok(val)
+    } else {
+        Err(serde::de::Error::custom("value must be between 0 and 1"))
+    }
+}
+
+fn validate_https_url<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<url::Url, D::Error> {
+    let s = compact_str::CompactString::deserialize(deserializer)?;
+    url::Url::parse(&s)
+        .ok()
+        .filter(|u| u.scheme() == "https" && u.host_str().is_some())
+        .ok_or_else(|| {
+            serde::de::Error::custom(compact_str::format_compact!(
+                "'{s}' is not a valid 'https' url"
+            ))
+        })
+}
+
+fn default_doh_cache_size() -> NonZero<usize> {
+    NonZero::new(256).unwrap()
+}
+
+fn default_balancer_max_retries() -> NonZero<usize> {
+    NonZero::new(3).unwrap()
+}
+
+fn default_max_cidr_hosts() -> u64 {
+    crate::parsers::DEFAULT_MAX_CIDR_HOSTS
+}
+
+fn default_retry_max_retries() -> usize {
+    2
+}
+
+fn default_retry_initial_delay() -> f64 {
+    0.5
+}
+
+fn default_retry_max_delay() -> f64 {
+    8.0
+}
+
+fn default_retry_jitter_fraction() -> f64 {
+    0.25
+}
+
+fn default_circuit_breaker_failure_threshold() -> NonZero<usize> {
+    NonZero::new(5).unwrap()
+}
+
+fn default_circuit_breaker_cooldown() -> f64 {
+    30.0
+}
+
 fn validate_proxy_url<'de, D: serde::Deserializer<'de>>(
     deserializer: D,
 ) -> Result<Option<url::Url>, D::Error> {
@@ -77,13 +155,24 @@ fn validate_http_url<'de, D: serde::Deserializer<'de>>(
     validate_url_generic(deserializer, &["http", "https"])
 }
 
+fn validate_socks5_url<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<url::Url>, D::Error> {
+    validate_url_generic(deserializer, &["socks5", "socks5h"])
+}
+
 #[derive(serde::Deserialize)]
 pub struct DetailedSourceConfig {
     pub url: String,
     #[serde(default)]
-    pub basic_auth: Option<BasicAuth>,
+    pub auth: Option<SourceAuth>,
     #[serde(default)]
     pub headers: Option<HashMap<String, String>>,
+    /// Name of a [`crate::connector::ProxyConnector`] registered via
+    /// [`crate::config::Config::register_connector`] that all proxies
+    /// scraped from this source should be checked through.
+    #[serde(default)]
+    pub custom_scheme: Option<String>,
 }
 
 #[derive(serde::Deserialize)]
@@ -99,16 +188,99 @@ pub struct ScrapingProtocolConfig {
     pub urls: Vec<SourceConfig>,
 }
 
+/// Tuning for the circuit breaker that [`crate::http::RetryMiddleware`]
+/// keeps per scraping-source host, so one unreachable host stops being
+/// retried instead of stalling the rest of the scrape.
+#[derive(serde::Deserialize)]
+pub struct CircuitBreakerConfig {
+    #[serde(default = "default_circuit_breaker_failure_threshold")]
+    pub failure_threshold: NonZero<usize>,
+    /// Seconds the circuit stays open after tripping. Doubles on each
+    /// consecutive trip for the same host, up to an internal ceiling.
+    #[serde(
+        default = "default_circuit_breaker_cooldown",
+        deserialize_with = "validate_positive_f64"
+    )]
+    pub cooldown: f64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: default_circuit_breaker_failure_threshold(),
+            cooldown: default_circuit_breaker_cooldown(),
+        }
+    }
+}
+
+/// Tuning for [`crate::http::RetryMiddleware`], which retries failed
+/// scraping requests with exponential backoff.
+#[derive(serde::Deserialize)]
+pub struct RetryConfig {
+    #[serde(default = "default_retry_max_retries")]
+    pub max_retries: usize,
+    #[serde(
+        default = "default_retry_initial_delay",
+        deserialize_with = "validate_positive_f64"
+    )]
+    pub initial_delay: f64,
+    #[serde(
+        default = "default_retry_max_delay",
+        deserialize_with = "validate_positive_f64"
+    )]
+    pub max_delay: f64,
+    #[serde(
+        default = "default_retry_jitter_fraction",
+        deserialize_with = "validate_unit_interval"
+    )]
+    pub jitter_fraction: f64,
+    /// Extra HTTP status codes to retry, beyond the built-in
+    /// 408/429/500/502/503/504 set.
+    #[serde(default)]
+    pub extra_retryable_statuses: Vec<u16>,
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: default_retry_max_retries(),
+            initial_delay: default_retry_initial_delay(),
+            max_delay: default_retry_max_delay(),
+            jitter_fraction: default_retry_jitter_fraction(),
+            extra_retryable_statuses: Vec::new(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+        }
+    }
+}
+
 #[derive(serde::Deserialize)]
 pub struct ScrapingConfig {
     pub max_proxies_per_source: usize,
+    /// Upper bound on a single source's response/file size in bytes. `0`
+    /// means unlimited. Protects against a hostile or misconfigured source
+    /// returning an unbounded amount of data.
+    #[serde(default)]
+    pub max_source_bytes: u64,
+    /// Upper bound on how many hosts a single CIDR range found in a
+    /// free-text source is allowed to expand to; an oversized range is
+    /// logged and left unexpanded (see [`crate::parsers::expand_cidr_ranges`]).
+    #[serde(default = "default_max_cidr_hosts")]
+    pub max_cidr_hosts: u64,
     #[serde(deserialize_with = "validate_positive_f64")]
     pub timeout: f64,
     #[serde(deserialize_with = "validate_positive_f64")]
     pub connect_timeout: f64,
     #[serde(deserialize_with = "validate_proxy_url")]
     pub proxy: Option<url::Url>,
+    /// Explicit credentials for `proxy`. When set, they take precedence over
+    /// any `user:pass@` embedded directly in its URL, which is used otherwise.
+    #[serde(default)]
+    pub proxy_auth: Option<BasicAuth>,
     pub user_agent: String,
+    #[serde(default)]
+    pub retry: RetryConfig,
 
     pub http: ScrapingProtocolConfig,
     pub socks4: ScrapingProtocolConfig,
@@ -125,11 +297,54 @@ pub struct CheckingConfig {
     #[serde(deserialize_with = "validate_positive_f64")]
     pub connect_timeout: f64,
     pub user_agent: String,
+    pub elite_only: bool,
+    /// When `true` and `check_url` is set, each working proxy is additionally
+    /// probed by tunnelling to `check_url` behind a PROXY protocol v2 header
+    /// advertising a synthetic client address, to see whether the proxy (or
+    /// whatever it's chained to) honours it.
+    #[serde(default)]
+    pub probe_proxy_protocol: bool,
+    /// Base64 SHA-256 hashes of the `check_url` leaf certificate's expected
+    /// SubjectPublicKeyInfo. When non-empty and `check_url` is `https://`,
+    /// proxies are checked through a pinned TLS verifier that rejects any
+    /// certificate whose SPKI doesn't match one of these pins, catching a
+    /// transparently intercepting proxy that re-signs TLS.
+    #[serde(default)]
+    pub spki_pins: Vec<String>,
 }
 
 #[derive(serde::Deserialize)]
 pub struct TxtOutputConfig {
     pub enabled: bool,
+    #[serde(default)]
+    pub compression: Compression,
+    /// When `true`, additionally writes each proxy into
+    /// `proxies/by_country/<ISO code>.txt` (or `unknown.txt` when the exit
+    /// IP doesn't resolve), reusing the same City reader as
+    /// `output.json.include_geolocation`.
+    #[serde(default)]
+    pub group_by_country: bool,
+    /// Same as `group_by_country`, but into
+    /// `proxies/by_asn/<ASN number>.txt`, reusing the ASN reader.
+    #[serde(default)]
+    pub group_by_asn: bool,
+}
+
+/// Restricts the final proxy list by the country/ASN of each proxy's exit
+/// IP, as resolved by the same GeoIP readers used for
+/// `output.json`/`output.txt` grouping. A proxy whose exit IP doesn't
+/// resolve is kept unless an allow-list is set (in which case it has
+/// nothing to match and is dropped).
+#[derive(Default, serde::Deserialize)]
+pub struct GeoFilterConfig {
+    #[serde(default)]
+    pub allowed_countries: Vec<String>,
+    #[serde(default)]
+    pub blocked_countries: Vec<String>,
+    #[serde(default)]
+    pub allowed_asns: Vec<u32>,
+    #[serde(default)]
+    pub blocked_asns: Vec<u32>,
 }
 
 #[derive(serde::Deserialize)]
@@ -137,6 +352,9 @@ pub struct JsonOutputConfig {
     pub enabled: bool,
     pub include_asn: bool,
     pub include_geolocation: bool,
+    pub include_anonymity: bool,
+    #[serde(default)]
+    pub compression: Compression,
 }
 
 pub struct OutputConfig {
@@ -144,6 +362,104 @@ pub struct OutputConfig {
     pub sort_by_speed: bool,
     pub txt: TxtOutputConfig,
     pub json: JsonOutputConfig,
+    pub geo_filter: GeoFilterConfig,
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct IntervalConfig {
+    #[serde(default)]
+    #[serde(deserialize_with = "validate_non_negative_f64")]
+    pub rerun_every: f64,
+}
+
+#[derive(serde::Deserialize)]
+pub struct DohConfig {
+    #[serde(deserialize_with = "validate_https_url")]
+    pub endpoint: url::Url,
+    #[serde(default = "default_doh_cache_size")]
+    pub cache_size: NonZero<usize>,
+    #[serde(default)]
+    pub overrides: HashMap<String, Vec<IpAddr>>,
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct DnsConfig {
+    #[serde(default)]
+    pub doh: Option<DohConfig>,
+}
+
+
+#[derive(serde::Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum DiscoveryBackendConfig {
+    Shodan {
+        api_key: String,
+        search_query: String,
+        #[serde(deserialize_with = "validate_positive_f64")]
+        rate_limit: f64,
+        max_results: usize,
+    },
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct DiscoveryConfig {
+    #[serde(default)]
+    pub backends: Vec<DiscoveryBackendConfig>,
+}
+
+/// Configuration for the optional local load-balancer server (see
+/// `balancer::Balancer`), which exposes the checked working proxies as a
+/// single rotating `SOCKS5`/HTTP listener.
+#[derive(serde::Deserialize)]
+pub struct BalancerConfig {
+    pub listen_addr: std::net::SocketAddr,
+    #[serde(default = "default_balancer_max_retries")]
+    pub max_retries: NonZero<usize>,
+}
+
+/// Where to obtain the GeoLite2 ASN/City `.mmdb` (see `ipdb::DbType`). When
+/// absent, falls back to downloading from the built-in public mirror, same
+/// as before this was configurable.
+#[derive(serde::Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum GeoIpSourceConfig {
+    /// MaxMind's own `download` endpoint, authenticated with an account ID
+    /// + license key. The response is a `.tar.gz` that gets unpacked to
+    /// extract the `.mmdb`.
+    Maxmind { account_id: String, license_key: String },
+    /// An arbitrary URL serving the `.mmdb` directly, optionally
+    /// `.gz`/`.zst`-compressed (detected from the URL suffix or the
+    /// response's `Content-Encoding`).
+    Url { url: String },
+    /// A pre-existing local `.mmdb` path. Skips downloading entirely; the
+    /// file is opened in place on every run, and a change in its
+    /// modification time is logged.
+    Path { path: PathBuf },
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct IpdbConfig {
+    #[serde(default)]
+    pub asn_source: Option<GeoIpSourceConfig>,
+    #[serde(default)]
+    pub geo_source: Option<GeoIpSourceConfig>,
+}
+
+/// Configuration for the optional status/progress HTTP server (see
+/// `status_server`), which exposes the live `AppEvent` stream that would
+/// otherwise only be visible in the TUI.
+#[derive(serde::Deserialize)]
+pub struct StatusServerConfig {
+    pub listen_addr: std::net::SocketAddr,
+}
+
+/// Configuration for the optional output-serving HTTP server (see
+/// `serve`), which exposes `all.txt`, the per-protocol txt files and
+/// `proxies.json` over HTTP with `ETag`/`If-None-Match` support, so
+/// downstream tools can poll instead of reading files off disk.
+#[derive(serde::Deserialize)]
+pub struct ServeConfig {
+    pub listen_addr: std::net::SocketAddr,
 }
 
 #[derive(serde::Deserialize)]
@@ -152,6 +468,24 @@ pub struct RawConfig {
     pub scraping: ScrapingConfig,
     pub checking: CheckingConfig,
     pub output: OutputConfig,
+    #[serde(default)]
+    pub interval: IntervalConfig,
+    #[serde(default)]
+    pub dns: DnsConfig,
+    #[serde(default)]
+    pub tls: TlsConfig,
+    #[serde(default)]
+    pub discovery: DiscoveryConfig,
+    #[serde(default)]
+    pub ipdb: IpdbConfig,
+    #[serde(deserialize_with = "validate_socks5_url")]
+    pub upstream_proxy: Option<url::Url>,
+    #[serde(default)]
+    pub balancer: Option<BalancerConfig>,
+    #[serde(default)]
+    pub status_server: Option<StatusServerConfig>,
+    #[serde(default)]
+    pub serve: Option<ServeConfig>,
 }
 
 #[expect(clippy::missing_trait_methods)]
@@ -165,6 +499,8 @@ impl<'de> serde::Deserialize<'de> for OutputConfig {
             pub sort_by_speed: bool,
             pub txt: TxtOutputConfig,
             pub json: JsonOutputConfig,
+            #[serde(default)]
+            pub geo_filter: GeoFilterConfig,
         }
 
         let inner = InnerOutputConfig::deserialize(deserializer)?;
@@ -174,12 +510,21 @@ impl<'de> serde::Deserialize<'de> for OutputConfig {
                  enabled in config",
             ));
         }
+        if (inner.txt.group_by_country || inner.txt.group_by_asn)
+            && !inner.txt.enabled
+        {
+            return Err(serde::de::Error::custom(
+                "'output.txt.group_by_country'/'group_by_asn' require \
+                 'output.txt' to be enabled",
+            ));
+        }
 
         Ok(Self {
             path: inner.path,
             sort_by_speed: inner.sort_by_speed,
             txt: inner.txt,
             json: inner.json,
+            geo_filter: inner.geo_filter,
         })
     }
 }
@@ -1,18 +1,85 @@
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
-use color_eyre::eyre::{OptionExt as _, WrapErr as _};
+use color_eyre::eyre::{OptionExt as _, WrapErr as _, eyre};
 use foldhash::HashSetExt as _;
+use futures::StreamExt as _;
 
 #[cfg(feature = "tui")]
 use crate::event::{AppEvent, Event};
 use crate::{
     HashSet,
     config::{Config, Source},
-    parsers::PROXY_REGEX,
+    http::SourceAuth,
+    parsers,
     proxy::{Proxy, ProxyType},
     utils::pretty_error,
 };
 
+/// Reads `response`'s body incrementally, aborting once more than
+/// `max_source_bytes` have been read (`0` means unlimited) instead of
+/// buffering an unbounded amount of data via `.text()`.
+async fn read_capped_response(
+    response: reqwest::Response,
+    max_source_bytes: u64,
+) -> crate::Result<String> {
+    if max_source_bytes != 0 {
+        if let Some(content_length) = response.content_length() {
+            if content_length > max_source_bytes {
+                return Err(eyre!(
+                    "response is {content_length} bytes, exceeding \
+                     max_source_bytes ({max_source_bytes})"
+                ));
+            }
+        }
+    }
+
+    let mut body = Vec::new();
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        body.extend_from_slice(&chunk);
+        if max_source_bytes != 0 && body.len() as u64 > max_source_bytes {
+            return Err(eyre!(
+                "response exceeds max_source_bytes ({max_source_bytes}) - \
+                 aborting"
+            ));
+        }
+    }
+    String::from_utf8(body).wrap_err("response is not valid UTF-8")
+}
+
+/// Refuses to read `path` if it is larger than `max_source_bytes` (`0`
+/// means unlimited), so a local source file can't silently OOM the
+/// process either.
+async fn read_capped_file(
+    path: impl AsRef<Path>,
+    max_source_bytes: u64,
+) -> crate::Result<String> {
+    let path = path.as_ref();
+    if max_source_bytes != 0 {
+        let metadata =
+            tokio::fs::metadata(path).await.wrap_err_with(|| {
+                compact_str::format_compact!(
+                    "failed to stat file: {}",
+                    path.display()
+                )
+            })?;
+        if metadata.len() > max_source_bytes {
+            return Err(eyre!(
+                "file is {} bytes, exceeding max_source_bytes ({max_source_bytes}): {}",
+                metadata.len(),
+                path.display()
+            ));
+        }
+    }
+    tokio::fs::read_to_string(path).await.wrap_err_with(move || {
+        compact_str::format_compact!(
+            "failed to read file to string: {}",
+            path.display()
+        )
+    })
+}
+
 async fn scrape_one(
     config: Arc<Config>,
     http_client: reqwest_middleware::ClientWithMiddleware,
@@ -20,16 +87,30 @@ async fn scrape_one(
     proxies: Arc<parking_lot::Mutex<HashSet<Proxy>>>,
     source: Arc<Source>,
     #[cfg(feature = "tui")] tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    #[cfg(feature = "tui")] pause: crate::pause::PauseControl,
 ) -> crate::Result<()> {
+    #[cfg(feature = "tui")]
+    pause.wait_if_paused().await;
+
     let text_result = if let Ok(u) = url::Url::parse(&source.url) {
         match u.scheme() {
             "http" | "https" => {
+                let url_str = u.as_str().to_owned();
                 let mut request = http_client.get(u);
                 drop(http_client);
 
-                if let Some(auth) = &source.basic_auth {
-                    request = request
-                        .basic_auth(&auth.username, auth.password.as_ref());
+                if let Some(auth) = &source.auth {
+                    request = match auth {
+                        SourceAuth::Basic { username, password } => {
+                            request.basic_auth(username, password.as_ref())
+                        }
+                        SourceAuth::Bearer { token } => {
+                            request.bearer_auth(token)
+                        }
+                        SourceAuth::Header { name, value } => {
+                            request.header(name.as_bytes(), value.as_bytes())
+                        }
+                    };
                 }
 
                 if let Some(headers) = &source.headers {
@@ -39,45 +120,86 @@ async fn scrape_one(
                 }
 
                 match request.send().await {
-                    Ok(resp) => resp.text().await.map_err(Into::into),
+                    Ok(resp) => {
+                        let format = parsers::SourceFormat::detect(
+                            resp.headers()
+                                .get(reqwest::header::CONTENT_TYPE)
+                                .and_then(|v| v.to_str().ok()),
+                            &url_str,
+                        );
+                        read_capped_response(
+                            resp,
+                            config.scraping.max_source_bytes,
+                        )
+                        .await
+                        .map(|text| (text, format))
+                    }
                     Err(e) => Err(e.into()),
                 }
             }
             _ => {
                 drop(http_client);
+                let format = parsers::SourceFormat::detect(None, u.as_str());
                 match u.to_file_path() {
-                    Ok(path) => tokio::fs::read_to_string(path)
+                    Ok(path) => {
+                        read_capped_file(
+                            path,
+                            config.scraping.max_source_bytes,
+                        )
                         .await
-                        .wrap_err_with(move || {
-                            compact_str::format_compact!(
-                                "failed to read file to string: {u}"
-                            )
-                        }),
-                    Err(()) => tokio::fs::read_to_string(&source.url)
+                        .map(|text| (text, format))
+                    }
+                    Err(()) => {
+                        read_capped_file(
+                            &source.url,
+                            config.scraping.max_source_bytes,
+                        )
                         .await
-                        .wrap_err_with(move || {
-                            compact_str::format_compact!(
-                                "failed to read file to string: {u}"
-                            )
-                        }),
+                        .map(|text| (text, format))
+                    }
                 }
             }
         }
     } else {
         drop(http_client);
-        tokio::fs::read_to_string(&source.url).await.wrap_err_with(|| {
-            compact_str::format_compact!(
-                "failed to read file to string: {}",
-                source.url
-            )
-        })
+        let format = parsers::SourceFormat::detect(None, &source.url);
+        read_capped_file(&source.url, config.scraping.max_source_bytes)
+            .await
+            .map(|text| (text, format))
     };
 
     #[cfg(feature = "tui")]
     drop(tx.send(Event::App(AppEvent::SourceScraped(proto))));
 
-    let text = match text_result {
-        Ok(text) => text,
+    let (text, format) = match text_result {
+        Ok(result) => result,
+        Err(e) => {
+            tracing::warn!("{}: {}", source.url, pretty_error(&e));
+            return Ok(());
+        }
+    };
+
+    let candidates = match format {
+        parsers::SourceFormat::Json => parsers::parse_json_proxies(
+            &text,
+            proto,
+            source.custom_scheme.as_ref(),
+        ),
+        parsers::SourceFormat::Csv => parsers::parse_csv_proxies(
+            &text,
+            proto,
+            source.custom_scheme.as_ref(),
+        ),
+        parsers::SourceFormat::Text => parsers::parse_text_proxies(
+            &parsers::expand_cidr_ranges(&text, config.scraping.max_cidr_hosts),
+            proto,
+            source.custom_scheme.as_ref(),
+        ),
+    };
+    drop(text);
+
+    let candidates = match candidates {
+        Ok(candidates) => candidates,
         Err(e) => {
             tracing::warn!("{}: {}", source.url, pretty_error(&e));
             return Ok(());
@@ -89,7 +211,7 @@ async fn scrape_one(
 
     let mut new_proxies = HashSet::new();
 
-    for maybe_capture in PROXY_REGEX.captures_iter(&text) {
+    for proxy in candidates {
         if config.scraping.max_proxies_per_source != 0
             && new_proxies.len() >= config.scraping.max_proxies_per_source
         {
@@ -101,39 +223,15 @@ async fn scrape_one(
             return Ok(());
         }
 
-        let capture = maybe_capture?;
-
-        let protocol = match capture.name("protocol") {
-            Some(m) => m.as_str().parse()?,
-            None => proto,
-        };
-
-        if config.protocol_is_enabled(protocol) {
+        if config.protocol_is_enabled(proxy.protocol) {
             #[cfg(feature = "tui")]
-            seen_protocols.insert(protocol);
-
-            new_proxies.insert(Proxy {
-                protocol,
-                host: capture
-                    .name("host")
-                    .ok_or_eyre("failed to match \"host\" regex capture group")?
-                    .as_str()
-                    .into(),
-                port: capture
-                    .name("port")
-                    .ok_or_eyre("failed to match \"port\" regex capture group")?
-                    .as_str()
-                    .parse()?,
-                username: capture.name("username").map(|m| m.as_str().into()),
-                password: capture.name("password").map(|m| m.as_str().into()),
-                timeout: None,
-                exit_ip: None,
-            });
+            seen_protocols.insert(proxy.protocol);
+
+            new_proxies.insert(proxy);
         }
     }
 
     drop(config);
-    drop(text);
 
     if new_proxies.is_empty() {
         tracing::warn!("{}: no proxies found", source.url);
@@ -161,6 +259,7 @@ pub async fn scrape_all(
     http_client: reqwest_middleware::ClientWithMiddleware,
     token: tokio_util::sync::CancellationToken,
     #[cfg(feature = "tui")] tx: tokio::sync::mpsc::UnboundedSender<Event>,
+    #[cfg(feature = "tui")] pause: crate::pause::PauseControl,
 ) -> crate::Result<Vec<Proxy>> {
     let proxies = Arc::new(parking_lot::Mutex::new(HashSet::new()));
 
@@ -177,6 +276,8 @@ pub async fn scrape_all(
             let source = Arc::clone(source);
             #[cfg(feature = "tui")]
             let tx = tx.clone();
+            #[cfg(feature = "tui")]
+            let pause = pause.clone();
             join_set.spawn(async move {
                 tokio::select! {
                     biased;
@@ -188,6 +289,8 @@ pub async fn scrape_all(
                         source,
                         #[cfg(feature = "tui")]
                         tx,
+                        #[cfg(feature = "tui")]
+                        pause,
                     ) => res,
                     () = token.cancelled() => Ok(()),
                 }
@@ -200,6 +303,8 @@ pub async fn scrape_all(
     drop(token);
     #[cfg(feature = "tui")]
     drop(tx);
+    #[cfg(feature = "tui")]
+    drop(pause);
 
     while let Some(res) = join_set.join_next().await {
         res??;
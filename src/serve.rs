@@ -0,0 +1,208 @@
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
+
+use axum::{
+    Router,
+    extract::State,
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use color_eyre::eyre::WrapErr as _;
+
+use crate::{
+    config::Config,
+    output::{compressed_path, etag_path},
+    utils::pretty_error,
+};
+
+#[derive(Clone, Copy)]
+enum ContentType {
+    Text,
+    Json,
+}
+
+impl ContentType {
+    const fn mime(self) -> &'static str {
+        match self {
+            Self::Text => "text/plain; charset=utf-8",
+            Self::Json => "application/json",
+        }
+    }
+}
+
+/// A `GET` endpoint backed by a single generated output file, read fresh
+/// off disk on every request rather than cached in memory, so it always
+/// reflects the most recent `save_proxies` run.
+struct ServedFile {
+    path: PathBuf,
+    content_type: ContentType,
+    content_encoding: Option<&'static str>,
+}
+
+impl ServedFile {
+    fn new(path: PathBuf, content_type: ContentType) -> Self {
+        let content_encoding = match path.extension().and_then(|e| e.to_str())
+        {
+            Some("gz") => Some("gzip"),
+            Some("zst") => Some("zstd"),
+            _ => None,
+        };
+        Self { path, content_type, content_encoding }
+    }
+}
+
+struct ServerState {
+    files: Vec<(String, ServedFile)>,
+}
+
+async fn serve_file(path: &std::path::Path) -> crate::Result<Option<Vec<u8>>> {
+    match tokio::fs::read(path).await {
+        Ok(data) => Ok(Some(data)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).wrap_err_with(|| {
+            format!("failed to read file: {}", path.display())
+        }),
+    }
+}
+
+async fn read_etag(path: &std::path::Path) -> crate::Result<Option<String>> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(etag) => Ok(Some(etag)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).wrap_err_with(|| {
+            format!("failed to read file: {}", path.display())
+        }),
+    }
+}
+
+async fn file_handler(
+    headers: HeaderMap,
+    file: &ServedFile,
+) -> crate::Result<Response> {
+    let current_etag = read_etag(&etag_path(&file.path)).await?;
+
+    if let Some(current_etag) = current_etag.as_deref()
+        && let Some(if_none_match) =
+            headers.get(header::IF_NONE_MATCH).and_then(|v| v.to_str().ok())
+        && if_none_match == current_etag
+    {
+        return Ok(StatusCode::NOT_MODIFIED.into_response());
+    }
+
+    let Some(data) = serve_file(&file.path).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    let mut response = data.into_response();
+    let response_headers = response.headers_mut();
+    response_headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(file.content_type.mime()),
+    );
+    response_headers
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    if let Some(content_encoding) = file.content_encoding {
+        response_headers.insert(
+            header::CONTENT_ENCODING,
+            HeaderValue::from_static(content_encoding),
+        );
+    }
+    if let Some(current_etag) = current_etag
+        && let Ok(value) = HeaderValue::from_str(&current_etag)
+    {
+        response_headers.insert(header::ETAG, value);
+    }
+    Ok(response)
+}
+
+async fn handler(
+    headers: HeaderMap,
+    State(state): State<Arc<ServerState>>,
+    uri: axum::http::Uri,
+) -> Response {
+    let Some((_, file)) =
+        state.files.iter().find(|(route, _)| route == uri.path())
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    match file_handler(headers, file).await {
+        Ok(response) => response,
+        Err(e) => {
+            tracing::warn!("failed to serve {}: {}", uri.path(), pretty_error(&e));
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Spawns the optional output-serving HTTP server, exposing `all.txt`,
+/// each enabled protocol's txt file, and `proxies.json` at
+/// `listen_addr`. Mirrors `DbType::download`'s `ETag`/`If-None-Match`
+/// mechanism: each file's strong ETag is the one `save_proxies` wrote
+/// alongside it (see `output::write_output`), so a client that already
+/// has the current version gets a `304 Not Modified` without the body
+/// being re-read, let alone regenerated.
+pub fn spawn(
+    config: &Config,
+    listen_addr: SocketAddr,
+    token: tokio_util::sync::CancellationToken,
+) {
+    let directory_path = config.output.path.join("proxies");
+    let mut files = Vec::new();
+
+    if config.output.txt.enabled {
+        files.push((
+            "/all.txt".to_owned(),
+            ServedFile::new(
+                compressed_path(
+                    directory_path.join("all.txt"),
+                    config.output.txt.compression,
+                ),
+                ContentType::Text,
+            ),
+        ));
+        for protocol in config.enabled_protocols().copied() {
+            let mut file_path = directory_path.join(protocol.as_str());
+            file_path.set_extension("txt");
+            files.push((
+                format!("/{}.txt", protocol.as_str()),
+                ServedFile::new(
+                    compressed_path(file_path, config.output.txt.compression),
+                    ContentType::Text,
+                ),
+            ));
+        }
+    }
+
+    if config.output.json.enabled {
+        files.push((
+            "/proxies.json".to_owned(),
+            ServedFile::new(
+                compressed_path(
+                    config.output.path.join("proxies.json"),
+                    config.output.json.compression,
+                ),
+                ContentType::Json,
+            ),
+        ));
+    }
+
+    let state = Arc::new(ServerState { files });
+    let app = Router::new().fallback(handler).with_state(state);
+
+    tokio::spawn(async move {
+        if let Err(e) = serve(listen_addr, app, token).await {
+            tracing::warn!("output server stopped: {}", pretty_error(&e));
+        }
+    });
+}
+
+async fn serve(
+    listen_addr: SocketAddr,
+    app: Router,
+    token: tokio_util::sync::CancellationToken,
+) -> crate::Result<()> {
+    let listener = tokio::net::TcpListener::bind(listen_addr).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move { token.cancelled().await })
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use color_eyre::eyre::{OptionExt as _, eyre};
+
+use crate::config::UpstreamProxyConfig;
+
+/// Tunnels every connection reqwest opens through a single SOCKS5
+/// `upstream_proxy` (e.g. a local Tor daemon) before the destination's own
+/// handshake runs on top of that stream. This is how two proxies get
+/// chained, since reqwest itself only ever dials one.
+#[derive(Clone)]
+pub struct UpstreamSocksLayer(Arc<UpstreamProxyConfig>);
+
+impl UpstreamSocksLayer {
+    pub fn new(upstream_proxy: Arc<UpstreamProxyConfig>) -> Self {
+        Self(upstream_proxy)
+    }
+}
+
+impl<S> tower::Layer<S> for UpstreamSocksLayer {
+    type Service = UpstreamSocksConnector;
+
+    fn layer(&self, _inner: S) -> Self::Service {
+        UpstreamSocksConnector(Arc::clone(&self.0))
+    }
+}
+
+#[derive(Clone)]
+pub struct UpstreamSocksConnector(Arc<UpstreamProxyConfig>);
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = crate::Result<T>> + Send>>;
+
+impl tower::Service<http::Uri> for UpstreamSocksConnector {
+    type Response = tokio_socks::tcp::Socks5Stream<tokio::net::TcpStream>;
+    type Error = crate::Error;
+    type Future = BoxFuture<Self::Response>;
+
+    fn poll_ready(
+        &mut self,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, uri: http::Uri) -> Self::Future {
+        let upstream = Arc::clone(&self.0);
+        Box::pin(async move { connect(&upstream, &uri).await })
+    }
+}
+
+async fn connect(
+    upstream: &UpstreamProxyConfig,
+    uri: &http::Uri,
+) -> crate::Result<tokio_socks::tcp::Socks5Stream<tokio::net::TcpStream>> {
+    let host = uri.host().ok_or_eyre("uri has no host")?;
+    let port = uri.port_u16().unwrap_or(match uri.scheme_str() {
+        Some("https") => 443,
+        _ => 80,
+    });
+
+    let upstream_host =
+        upstream.url.host_str().ok_or_eyre("upstream_proxy url has no host")?;
+    let upstream_port = upstream
+        .url
+        .port_or_known_default()
+        .ok_or_eyre("upstream_proxy url has no port")?;
+    let upstream_addr = format!("{upstream_host}:{upstream_port}");
+
+    let resolved_addr;
+    let target: tokio_socks::TargetAddr<'_> = if upstream.remote_dns {
+        tokio_socks::TargetAddr::Domain(host.into(), port)
+    } else {
+        resolved_addr = tokio::net::lookup_host((host, port))
+            .await?
+            .next()
+            .ok_or_eyre("failed to resolve host locally")?;
+        tokio_socks::TargetAddr::Ip(resolved_addr)
+    };
+
+    let stream = if let Some(password) = upstream.url.password() {
+        tokio_socks::tcp::Socks5Stream::connect_with_password(
+            upstream_addr.as_str(),
+            target,
+            upstream.url.username(),
+            password,
+        )
+        .await
+    } else {
+        tokio_socks::tcp::Socks5Stream::connect(upstream_addr.as_str(), target)
+            .await
+    }
+    .map_err(|e| {
+        eyre!("failed to connect to {host}:{port} via upstream_proxy: {e}")
+    })?;
+
+    Ok(stream)
+}
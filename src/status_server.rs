@@ -0,0 +1,115 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{
+    Router,
+    extract::{
+        State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    response::{IntoResponse, Json},
+    routing::get,
+};
+
+use crate::{
+    event::{AppEvent, Event},
+    tui::AppState,
+    utils::pretty_error,
+};
+
+struct ServerState {
+    app_state: parking_lot::RwLock<AppState>,
+    events: tokio::sync::broadcast::Sender<AppEvent>,
+}
+
+async fn status_handler(
+    State(state): State<Arc<ServerState>>,
+) -> Json<AppState> {
+    Json(state.app_state.read().clone())
+}
+
+async fn ws_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<Arc<ServerState>>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| stream_events(socket, state))
+}
+
+async fn stream_events(mut socket: WebSocket, state: Arc<ServerState>) {
+    let mut events = state.events.subscribe();
+    drop(state);
+    loop {
+        let app_event = match events.recv().await {
+            Ok(app_event) => app_event,
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {
+                continue;
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+        };
+
+        let Ok(text) = serde_json::to_string(&app_event) else { continue };
+        if socket.send(Message::Text(text.into())).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Fans out `rx` to both the returned receiver (which the caller should pass
+/// to [`crate::tui::run`] as usual) and `listen_addr`'s `GET /status`
+/// (current [`AppState`] snapshot) and `GET /ws` (a live stream of every
+/// [`AppEvent`]) endpoints, so external dashboards can observe a run
+/// without attaching to the terminal.
+pub fn spawn(
+    listen_addr: SocketAddr,
+    mut rx: tokio::sync::mpsc::UnboundedReceiver<Event>,
+    token: tokio_util::sync::CancellationToken,
+) -> tokio::sync::mpsc::UnboundedReceiver<Event> {
+    let (ui_tx, ui_rx) = tokio::sync::mpsc::unbounded_channel();
+    let (events_tx, _) = tokio::sync::broadcast::channel(1024);
+    let state = Arc::new(ServerState {
+        app_state: parking_lot::RwLock::new(AppState::default()),
+        events: events_tx,
+    });
+
+    tokio::spawn({
+        let state = Arc::clone(&state);
+        async move {
+            while let Some(event) = rx.recv().await {
+                if let Event::App(ref app_event) = event {
+                    crate::tui::apply_app_event(
+                        &mut state.app_state.write(),
+                        app_event.clone(),
+                    );
+                    drop(state.events.send(app_event.clone()));
+                }
+                if ui_tx.send(event).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        if let Err(e) = serve(listen_addr, state, token).await {
+            tracing::warn!("status server stopped: {}", pretty_error(&e));
+        }
+    });
+
+    ui_rx
+}
+
+async fn serve(
+    listen_addr: SocketAddr,
+    state: Arc<ServerState>,
+    token: tokio_util::sync::CancellationToken,
+) -> crate::Result<()> {
+    let app = Router::new()
+        .route("/status", get(status_handler))
+        .route("/ws", get(ws_handler))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(listen_addr).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move { token.cancelled().await })
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,68 @@
+//! `sd_notify` integration for running under systemd. Every function here
+//! is always callable; outside Linux builds with the `systemd` feature
+//! enabled (and outside a unit that actually sets `NOTIFY_SOCKET`), they're
+//! no-ops, so call sites never need their own `#[cfg]`.
+
+#[cfg(all(feature = "systemd", target_os = "linux"))]
+mod imp {
+    use std::time::Duration;
+
+    use crate::utils::pretty_error;
+
+    fn notify(states: &[sd_notify::NotifyState<'_>]) {
+        if let Err(e) = sd_notify::notify(false, states) {
+            tracing::debug!(
+                "sd_notify failed (expected when not running under \
+                 systemd): {}",
+                pretty_error(&e.into())
+            );
+        }
+    }
+
+    /// Tells systemd the service has finished its first scrape/check cycle
+    /// and written its output, so a unit with `Type=notify` considers it
+    /// started.
+    pub fn notify_ready() {
+        notify(&[sd_notify::NotifyState::Ready]);
+    }
+
+    /// Pushes a one-line human-readable status, shown by `systemctl status`.
+    pub fn notify_status(status: &str) {
+        notify(&[sd_notify::NotifyState::Status(status)]);
+    }
+
+    /// If the unit's `WatchdogSec=` is set (exposed to us as
+    /// `WATCHDOG_USEC`), spawns a task that pings `WATCHDOG=1` at half that
+    /// interval for as long as `token` isn't cancelled, so systemd restarts
+    /// the process if a check run ever stalls hard enough to stop pinging.
+    pub fn spawn_watchdog(token: tokio_util::sync::CancellationToken) {
+        let watchdog_usec = sd_notify::watchdog_enabled(false);
+        if watchdog_usec == 0 {
+            return;
+        }
+        let ping_every = Duration::from_micros(watchdog_usec) / 2;
+
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    biased;
+                    () = token.cancelled() => break,
+                    () = tokio::time::sleep(ping_every) => {
+                        notify(&[sd_notify::NotifyState::Watchdog]);
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(not(all(feature = "systemd", target_os = "linux")))]
+mod imp {
+    pub const fn notify_ready() {}
+
+    pub const fn notify_status(_status: &str) {}
+
+    pub fn spawn_watchdog(_token: tokio_util::sync::CancellationToken) {}
+}
+
+pub use imp::{notify_ready, notify_status, spawn_watchdog};
@@ -0,0 +1,167 @@
+use std::{path::PathBuf, sync::Arc};
+
+use base64::Engine as _;
+use rustls::{
+    DigitallySignedStruct, SignatureScheme,
+    client::danger::{
+        HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+    },
+    pki_types::{CertificateDer, ServerName, UnixTime},
+};
+use sha2::{Digest as _, Sha256};
+
+/// Substring every SPKI pin-mismatch error contains, so callers can tell it
+/// apart from an ordinary connection failure just by looking at the
+/// rendered [`crate::utils::pretty_error`] chain.
+pub const PIN_MISMATCH_MARKER: &str =
+    "did not match any configured checking.spki_pins";
+
+#[derive(Debug)]
+struct PinMismatchError;
+
+impl std::fmt::Display for PinMismatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "certificate's SubjectPublicKeyInfo {PIN_MISMATCH_MARKER}"
+        )
+    }
+}
+
+impl std::error::Error for PinMismatchError {}
+
+/// Does normal WebPKI chain validation, then additionally DER-extracts the
+/// leaf's SubjectPublicKeyInfo, hashes it with SHA-256 and base64-encodes
+/// it, failing the handshake unless it matches one of `pins`. Catches a
+/// transparently intercepting proxy that re-signs TLS with a
+/// trusted-but-unexpected certificate.
+#[derive(Debug)]
+struct SpkiPinningVerifier {
+    inner: Arc<rustls::client::WebPkiServerVerifier>,
+    pins: Vec<compact_str::CompactString>,
+}
+
+impl SpkiPinningVerifier {
+    fn check_pin(
+        &self,
+        cert: &CertificateDer<'_>,
+    ) -> Result<(), rustls::Error> {
+        let (_, parsed) = x509_parser::parse_x509_certificate(cert.as_ref())
+            .map_err(|e| {
+                rustls::Error::General(format!(
+                    "failed to parse certificate: {e}"
+                ))
+            })?;
+        let digest = base64::engine::general_purpose::STANDARD
+            .encode(Sha256::digest(parsed.public_key().raw));
+
+        if self.pins.iter().any(|pin| pin.as_str() == digest) {
+            Ok(())
+        } else {
+            Err(rustls::Error::InvalidCertificate(
+                rustls::CertificateError::Other(rustls::OtherError(Arc::new(
+                    PinMismatchError,
+                ))),
+            ))
+        }
+    }
+}
+
+impl ServerCertVerifier for SpkiPinningVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        )?;
+        self.check_pin(end_entity)?;
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+/// Which TLS implementation [`crate::http::create_reqwest_client`] builds
+/// its scraping client with.
+#[derive(Clone, Copy, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TlsBackend {
+    #[default]
+    Rustls,
+    NativeTls,
+}
+
+/// Which trust roots the `rustls` backend validates server certificates
+/// against. Ignored when `backend = "nativetls"`, which always defers to
+/// the OS trust store.
+#[derive(Clone, Copy, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TlsRootStore {
+    /// Roots loaded from the OS trust store, à la `rustls-native-certs`.
+    #[default]
+    Native,
+    /// The bundled Mozilla root set shipped by `webpki-roots`, useful in
+    /// minimal containers without an OS trust store.
+    WebpkiBundled,
+}
+
+#[derive(Default, serde::Deserialize)]
+pub struct TlsConfig {
+    #[serde(default)]
+    pub backend: TlsBackend,
+    #[serde(default)]
+    pub root_store: TlsRootStore,
+    /// Extra PEM-encoded CA certificates to trust, e.g. for a corporate MITM
+    /// proxy or an internal scraping source with a private CA.
+    #[serde(default)]
+    pub extra_ca_certs: Vec<PathBuf>,
+}
+
+pub fn build_pinned_client_config(
+    pins: Vec<compact_str::CompactString>,
+) -> crate::Result<rustls::ClientConfig> {
+    let mut root_store = rustls::RootCertStore::empty();
+    root_store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+
+    let inner = rustls::client::WebPkiServerVerifier::builder(Arc::new(
+        root_store,
+    ))
+    .build()?;
+
+    Ok(rustls::ClientConfig::builder()
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(SpkiPinningVerifier {
+            inner,
+            pins,
+        }))
+        .with_no_client_auth())
+}
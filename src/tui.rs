@@ -6,23 +6,24 @@
 
 use std::time::Duration;
 
-use crossterm::event::{
-    Event as CrosstermEvent, KeyCode, KeyModifiers, MouseEventKind,
-};
+use crossterm::event::{Event as CrosstermEvent, MouseEventKind};
 use futures::StreamExt as _;
 use ratatui::{
     Frame,
-    layout::{Alignment, Constraint, Direction, Layout},
-    style::{Color, Style},
-    text::{Line, Text},
-    widgets::{Block, Gauge},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    widgets::Block,
 };
-use tui_logger::{TuiLoggerWidget, TuiWidgetEvent, TuiWidgetState};
 
 use crate::{
     HashMap,
+    components::{
+        Component as _, FpsComponent, HotkeysComponent, InspectorComponent,
+        IpDbComponent, LogsComponent, ProxyColumnsComponent,
+    },
     event::{AppEvent, Event},
     ipdb,
+    keymap::{Action, Keymap},
+    pause::PauseControl,
     proxy::ProxyType,
 };
 
@@ -35,23 +36,64 @@ impl Drop for RatatuiRestoreGuard {
     }
 }
 
+/// Owns every [`crate::components::Component`], replacing the single
+/// `draw`/`handle_event` pair this module used to have. New panels are
+/// added here rather than by editing [`draw`]/[`handle_event`] directly.
+#[derive(Default)]
+struct Components {
+    logs: LogsComponent,
+    ipdb: IpDbComponent,
+    proxies: ProxyColumnsComponent,
+    hotkeys: HotkeysComponent,
+    inspector: InspectorComponent,
+    fps: FpsComponent,
+}
+
+impl Components {
+    /// Tries each component in turn, stopping at the first one that
+    /// claims `action`.
+    fn handle_action(&mut self, action: Action, state: &mut AppState) -> bool {
+        self.logs.handle_action(action, state)
+            || self.inspector.handle_action(action, state)
+            || self.fps.handle_action(action, state)
+    }
+
+    fn on_tick(&mut self, state: &mut AppState) {
+        self.logs.on_tick(state);
+        self.ipdb.on_tick(state);
+        self.proxies.on_tick(state);
+        self.hotkeys.on_tick(state);
+        self.inspector.on_tick(state);
+        self.fps.on_tick(state);
+    }
+}
+
 pub async fn run(
     mut terminal: ratatui::DefaultTerminal,
     token: tokio_util::sync::CancellationToken,
     tx: tokio::sync::mpsc::UnboundedSender<Event>,
     mut rx: tokio::sync::mpsc::UnboundedReceiver<Event>,
+    keymap: Keymap,
+    pause: PauseControl,
 ) -> crate::Result<()> {
     tokio::spawn(tick_event_listener(tx.clone()));
     tokio::spawn(crossterm_event_listener(tx));
 
     let mut app_state = AppState::default();
-    let logger_state = TuiWidgetState::default();
+    let mut components = Components::default();
 
     while !matches!(app_state.mode, AppMode::Quit) {
         if let Some(event) = rx.recv().await {
-            if handle_event(event, &mut app_state, &token, &logger_state) {
+            if handle_event(
+                event,
+                &mut app_state,
+                &token,
+                &mut components,
+                &keymap,
+                &pause,
+            ) {
                 terminal
-                    .draw(|frame| draw(frame, &app_state, &logger_state))?;
+                    .draw(|frame| draw(frame, &app_state, &components))?;
             }
         } else {
             break;
@@ -60,10 +102,14 @@ pub async fn run(
     Ok(())
 }
 
-#[derive(Default)]
+#[derive(Clone, Default, serde::Serialize)]
 pub enum AppMode {
     #[default]
     Running,
+    /// Scraping/checking workers are suspended via [`crate::pause::PauseControl`]
+    /// but the run hasn't been cancelled. Toggled by [`Action::TogglePause`],
+    /// distinct from the `next`-driven Running/Done/Quit lifecycle below.
+    Paused,
     Done,
     Quit,
 }
@@ -71,16 +117,58 @@ pub enum AppMode {
 impl AppMode {
     pub const fn next(&self) -> Self {
         match self {
-            Self::Running => Self::Done,
+            Self::Running | Self::Paused => Self::Done,
             Self::Done | Self::Quit => Self::Quit,
         }
     }
 }
 
-#[derive(Default)]
+/// One row of the drill-down inspector opened by [`Action::ToggleInspector`],
+/// carried in full by [`AppEvent::ProxyWorkingDetails`] as each proxy passes
+/// checking.
+#[derive(Clone, serde::Serialize)]
+pub struct WorkingProxyDetail {
+    pub protocol: ProxyType,
+    pub addr: compact_str::CompactString,
+    pub latency_secs: f64,
+    pub country: Option<compact_str::CompactString>,
+    pub asn: Option<u32>,
+    pub anonymity: Option<crate::proxy::Anonymity>,
+}
+
+/// Sort order for the inspector's rows, cycled by [`Action::InspectorCycleSort`].
+#[derive(Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub enum InspectorSort {
+    #[default]
+    Latency,
+    Country,
+}
+
+impl InspectorSort {
+    pub(crate) const fn next(self) -> Self {
+        match self {
+            Self::Latency => Self::Country,
+            Self::Country => Self::Latency,
+        }
+    }
+
+    pub(crate) const fn as_str(self) -> &'static str {
+        match self {
+            Self::Latency => "latency",
+            Self::Country => "country",
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
 pub struct AppState {
     pub mode: AppMode,
 
+    /// When this [`AppState`] was created, used to derive the checking
+    /// rate/ETA shown on the "Checking proxies" gauge.
+    #[serde(skip)]
+    pub start: std::time::Instant,
+
     pub asn_db_total: u64,
     pub asn_db_downloaded: usize,
 
@@ -93,6 +181,84 @@ pub struct AppState {
     pub proxies_total: HashMap<ProxyType, usize>,
     pub proxies_checked: HashMap<ProxyType, usize>,
     pub proxies_working: HashMap<ProxyType, usize>,
+
+    pub exit_ips_resolved: HashMap<ProxyType, usize>,
+    pub elite_proxies: HashMap<ProxyType, usize>,
+    pub anonymous_proxies: HashMap<ProxyType, usize>,
+    pub transparent_proxies: HashMap<ProxyType, usize>,
+    pub proxy_protocol_supported: HashMap<ProxyType, usize>,
+
+    /// Whether the inspector table (rather than the gauge dashboard) is
+    /// currently shown, toggled by [`Action::ToggleInspector`].
+    pub inspector_active: bool,
+    pub inspector_sort: InspectorSort,
+    pub inspector_scroll: usize,
+    pub working_proxies: Vec<WorkingProxyDetail>,
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self {
+            mode: AppMode::default(),
+            start: std::time::Instant::now(),
+            asn_db_total: 0,
+            asn_db_downloaded: 0,
+            geo_db_total: 0,
+            geo_db_downloaded: 0,
+            sources_total: HashMap::default(),
+            sources_scraped: HashMap::default(),
+            proxies_total: HashMap::default(),
+            proxies_checked: HashMap::default(),
+            proxies_working: HashMap::default(),
+            exit_ips_resolved: HashMap::default(),
+            elite_proxies: HashMap::default(),
+            anonymous_proxies: HashMap::default(),
+            transparent_proxies: HashMap::default(),
+            proxy_protocol_supported: HashMap::default(),
+            inspector_active: false,
+            inspector_sort: InspectorSort::default(),
+            inspector_scroll: 0,
+            working_proxies: Vec::new(),
+        }
+    }
+}
+
+/// Humanizes a [`Duration`] as its two most significant nonzero
+/// hour/minute/second units (e.g. `1h 3m`, `12s`), in the style of
+/// reel-moby's `format_time_nice`.
+fn format_time_nice(duration: Duration) -> String {
+    let total_secs = duration.as_secs();
+    let units =
+        [(total_secs / 3600, "h"), (total_secs % 3600 / 60, "m"), (total_secs % 60, "s")];
+    let mut parts = units.into_iter().filter(|(value, _)| *value > 0).take(2);
+    match (parts.next(), parts.next()) {
+        (Some((v1, u1)), Some((v2, u2))) => format!("{v1}{u1} {v2}{u2}"),
+        (Some((v1, u1)), None) => format!("{v1}{u1}"),
+        (None, _) => "0s".to_owned(),
+    }
+}
+
+/// Label for the "Checking proxies" gauge: counts plus a live
+/// proxies-per-second rate and ETA, `—` for the latter two until there's
+/// enough data (nothing checked yet, or no time has elapsed). Used by
+/// [`crate::components::ProxyColumnsComponent`].
+pub(crate) fn checking_progress_label(
+    proxies_checked: usize,
+    proxies_total: usize,
+    elapsed: Duration,
+) -> String {
+    let rate = if proxies_checked == 0 || elapsed.as_secs_f64() <= 0.0 {
+        0.0
+    } else {
+        (proxies_checked as f64) / elapsed.as_secs_f64()
+    };
+    if rate <= 0.0 {
+        return format!("{proxies_checked}/{proxies_total} | — | ETA —");
+    }
+    let remaining = proxies_total.saturating_sub(proxies_checked);
+    let eta =
+        format_time_nice(Duration::from_secs_f64((remaining as f64) / rate));
+    format!("{proxies_checked}/{proxies_total} | {rate:.1}/s | ETA {eta}")
 }
 
 async fn tick_event_listener(tx: tokio::sync::mpsc::UnboundedSender<Event>) {
@@ -139,7 +305,11 @@ async fn crossterm_event_listener(
     }
 }
 
-fn draw(f: &mut Frame<'_>, state: &AppState, logger_state: &TuiWidgetState) {
+/// Width of the top-right FPS/diagnostics overlay rendered by
+/// [`FpsComponent`] over the title bar.
+const FPS_OVERLAY_WIDTH: u16 = 16;
+
+fn draw(f: &mut Frame<'_>, state: &AppState, components: &Components) {
     let outer_block = Block::default()
         .title("https://github.com/monosans/proxy-scraper-checker")
         .title_alignment(Alignment::Center);
@@ -154,191 +324,86 @@ fn draw(f: &mut Frame<'_>, state: &AppState, logger_state: &TuiWidgetState) {
             // Scraping and checking
             Constraint::Length(1 + (3 * 3) + 1),
             // Hotkeys
-            Constraint::Length(4),
+            Constraint::Length(5),
         ])
         .split(outer_block.inner(f.area()));
     drop(outer_block);
 
-    f.render_widget(
-        TuiLoggerWidget::default()
-            .state(logger_state)
-            .block(Block::bordered().title("Logs"))
-            .output_file(false)
-            .output_line(false)
-            .style_trace(Style::default().fg(Color::Magenta))
-            .style_debug(Style::default().fg(Color::Green))
-            .style_info(Style::default().fg(Color::Cyan))
-            .style_warn(Style::default().fg(Color::Yellow))
-            .style_error(Style::default().fg(Color::Red)),
-        outer_layout[0],
-    );
-
-    let ipdb_layout = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Fill(1); 2])
-        .split(outer_layout[1]);
-    f.render_widget(
-        Gauge::default()
-            .block(Block::bordered().title("ASN database download"))
-            .ratio({
-                if state.asn_db_total == 0 {
-                    1.0
-                } else {
-                    (state.asn_db_downloaded as f64)
-                        / (state.asn_db_total as f64)
-                }
-            }),
-        ipdb_layout[0],
-    );
-    f.render_widget(
-        Gauge::default()
-            .block(Block::bordered().title("Geolocation database download"))
-            .ratio({
-                if state.geo_db_total == 0 {
-                    1.0
-                } else {
-                    (state.geo_db_downloaded as f64)
-                        / (state.geo_db_total as f64)
-                }
-            }),
-        ipdb_layout[1],
-    );
-    drop(ipdb_layout);
-
-    let mut proxy_types: Vec<_> = state.sources_total.keys().collect();
-    proxy_types.sort_unstable();
-
-    let proxies_layout = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints(proxy_types.iter().map(|_| Constraint::Fill(1)))
-        .split(outer_layout[2]);
-
-    for (i, proxy_type) in proxy_types.into_iter().enumerate() {
-        let block = Block::bordered().title(proxy_type.as_str().to_uppercase());
-        f.render_widget(block.clone(), proxies_layout[i]);
-
-        let layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Fill(1); 3])
-            .split(block.inner(proxies_layout[i]));
-        drop(block);
-
-        let sources_scraped =
-            state.sources_scraped.get(proxy_type).copied().unwrap_or_default();
-        let sources_total =
-            state.sources_total.get(proxy_type).copied().unwrap_or_default();
-
-        f.render_widget(
-            Gauge::default()
-                .ratio({
-                    if sources_total == 0 {
-                        1.0
-                    } else {
-                        (sources_scraped as f64) / (sources_total as f64)
-                    }
-                })
-                .block(Block::bordered().title("Scraping sources"))
-                .label(format!("{sources_scraped}/{sources_total}")),
-            layout[0],
-        );
-
-        let proxies_total =
-            state.proxies_total.get(proxy_type).copied().unwrap_or_default();
-        let proxies_checked =
-            state.proxies_checked.get(proxy_type).copied().unwrap_or_default();
-        f.render_widget(
-            Gauge::default()
-                .ratio({
-                    if proxies_total == 0 {
-                        1.0
-                    } else {
-                        (proxies_checked as f64) / (proxies_total as f64)
-                    }
-                })
-                .block(Block::bordered().title("Checking proxies"))
-                .label(format!("{proxies_checked}/{proxies_total}")),
-            layout[1],
-        );
-
-        let working_proxies_block = Block::bordered().title("Working proxies");
-        f.render_widget(working_proxies_block.clone(), layout[2]);
-
-        let proxies_working =
-            state.proxies_working.get(proxy_type).copied().unwrap_or_default();
-        f.render_widget(
-            Line::from(format!("{} ({:.1}%)", proxies_working, {
-                if proxies_checked == 0 {
-                    0.0_f64
-                } else {
-                    (proxies_working as f64) / (proxies_checked as f64)
-                        * 100.0_f64
-                }
-            }))
-            .alignment(Alignment::Center),
-            working_proxies_block.inner(layout[2]),
-        );
+    components.logs.draw(f, state, outer_layout[0]);
+    components.ipdb.draw(f, state, outer_layout[1]);
+    if state.inspector_active {
+        components.inspector.draw(f, state, outer_layout[2]);
+    } else {
+        components.proxies.draw(f, state, outer_layout[2]);
     }
+    components.hotkeys.draw(f, state, outer_layout[3]);
+
+    let fps_area = Rect {
+        x: f.area().width.saturating_sub(FPS_OVERLAY_WIDTH),
+        y: 0,
+        width: FPS_OVERLAY_WIDTH.min(f.area().width),
+        height: 1,
+    };
+    components.fps.draw(f, state, fps_area);
+}
 
-    drop(proxies_layout);
-
-    let running = matches!(state.mode, AppMode::Running);
-    let mut lines = Vec::with_capacity(if running { 4 } else { 3 });
-    lines.push(Line::from("Up / PageUp / k - scroll logs up"));
-    lines.push(Line::from("Down / PageDown / j - scroll logs down"));
-    if running {
-        lines.push(
-            Line::from("ESC / q - stop")
-                .style(Style::default().fg(Color::Yellow)),
-        );
+fn dispatch_action(
+    action: Action,
+    state: &mut AppState,
+    token: &tokio_util::sync::CancellationToken,
+    components: &mut Components,
+    pause: &PauseControl,
+) {
+    match action {
+        Action::Stop => {
+            state.mode = state.mode.next();
+            token.cancel();
+        }
+        Action::Quit => {
+            state.mode = AppMode::Quit;
+            token.cancel();
+        }
+        Action::TogglePause => {
+            if matches!(state.mode, AppMode::Running | AppMode::Paused) {
+                pause.toggle();
+                state.mode = if matches!(state.mode, AppMode::Paused) {
+                    AppMode::Running
+                } else {
+                    AppMode::Paused
+                };
+            }
+        }
+        _ => {
+            components.handle_action(action, state);
+        }
     }
-    lines.push(
-        Line::from(if running {
-            "Ctrl-C - quit"
-        } else {
-            "ESC / q / Ctrl-C - quit"
-        })
-        .style(Style::default().fg(Color::Red)),
-    );
-
-    f.render_widget(Text::from(lines).centered(), outer_layout[3]);
 }
 
 fn handle_event(
     event: Event,
     state: &mut AppState,
     token: &tokio_util::sync::CancellationToken,
-    logger_state: &TuiWidgetState,
+    components: &mut Components,
+    keymap: &Keymap,
+    pause: &PauseControl,
 ) -> bool {
     match event {
-        Event::Tick => true,
+        Event::Tick => {
+            components.on_tick(state);
+            true
+        }
         Event::Crossterm(crossterm_event) => {
             match crossterm_event {
-                CrosstermEvent::Key(key_event) => match key_event.code {
-                    KeyCode::Esc | KeyCode::Char('q' | 'Q') => {
-                        state.mode = state.mode.next();
-                        token.cancel();
-                    }
-                    KeyCode::Char('c' | 'C')
-                        if key_event.modifiers == KeyModifiers::CONTROL =>
+                CrosstermEvent::Key(key_event) => {
+                    if let Some(action) =
+                        keymap.action(key_event.code, key_event.modifiers)
                     {
-                        state.mode = AppMode::Quit;
-                        token.cancel();
-                    }
-                    KeyCode::Up | KeyCode::PageUp | KeyCode::Char('k') => {
-                        logger_state.transition(TuiWidgetEvent::PrevPageKey);
+                        dispatch_action(action, state, token, components, pause);
                     }
-                    KeyCode::Down | KeyCode::PageDown | KeyCode::Char('j') => {
-                        logger_state.transition(TuiWidgetEvent::NextPageKey);
-                    }
-                    _ => {}
-                },
+                }
                 CrosstermEvent::Mouse(mouse_event) => match mouse_event.kind {
-                    MouseEventKind::ScrollUp => {
-                        logger_state.transition(TuiWidgetEvent::PrevPageKey);
-                    }
-                    MouseEventKind::ScrollDown => {
-                        logger_state.transition(TuiWidgetEvent::NextPageKey);
-                    }
+                    MouseEventKind::ScrollUp => components.logs.scroll_up(),
+                    MouseEventKind::ScrollDown => components.logs.scroll_down(),
                     _ => {}
                 },
                 _ => {}
@@ -346,58 +411,123 @@ fn handle_event(
             false
         }
         Event::App(app_event) => {
-            match app_event {
-                AppEvent::IpDbTotal(ipdb::DbType::Asn, bytes) => {
-                    state.asn_db_total = bytes.unwrap_or_default();
-                }
-                AppEvent::IpDbTotal(ipdb::DbType::Geo, bytes) => {
-                    state.geo_db_total = bytes.unwrap_or_default();
-                }
-                AppEvent::IpDbDownloaded(ipdb::DbType::Asn, bytes) => {
-                    state.asn_db_downloaded =
-                        state.asn_db_downloaded.saturating_add(bytes);
-                }
-                AppEvent::IpDbDownloaded(ipdb::DbType::Geo, bytes) => {
-                    state.geo_db_downloaded =
-                        state.geo_db_downloaded.saturating_add(bytes);
-                }
-                AppEvent::SourcesTotal(proxy_type, amount) => {
-                    state.sources_total.insert(proxy_type, amount);
-                }
-                AppEvent::SourceScraped(proxy_type) => {
-                    state
-                        .sources_scraped
-                        .entry(proxy_type)
-                        .and_modify(|c| *c = c.saturating_add(1))
-                        .or_insert(1);
-                }
-                AppEvent::TotalProxies(proxy_type, amount) => {
-                    state.proxies_total.insert(proxy_type, amount);
-                }
-                AppEvent::ProxyChecked(proxy_type) => {
-                    state
-                        .proxies_checked
-                        .entry(proxy_type)
-                        .and_modify(|c| *c = c.saturating_add(1))
-                        .or_insert(1);
-                }
-                AppEvent::ProxyWorking(proxy_type) => {
-                    state
-                        .proxies_working
-                        .entry(proxy_type)
-                        .and_modify(|c| *c = c.saturating_add(1))
-                        .or_insert(1);
+            apply_app_event(state, app_event);
+            false
+        }
+    }
+}
+
+/// Accumulates a single [`AppEvent`] into `state`. Pulled out of
+/// [`handle_event`] so [`crate::status_server`] can maintain its own
+/// `AppState` snapshot from the same events without depending on the
+/// terminal-specific parts of `handle_event` (key handling, redraw
+/// signalling).
+pub(crate) fn apply_app_event(state: &mut AppState, app_event: AppEvent) {
+    match app_event {
+        AppEvent::IpDbTotal(ipdb::DbType::Asn, bytes) => {
+            state.asn_db_total = bytes.unwrap_or_default();
+        }
+        AppEvent::IpDbTotal(ipdb::DbType::Geo, bytes) => {
+            state.geo_db_total = bytes.unwrap_or_default();
+        }
+        AppEvent::IpDbDownloaded(ipdb::DbType::Asn, bytes) => {
+            state.asn_db_downloaded =
+                state.asn_db_downloaded.saturating_add(bytes);
+        }
+        AppEvent::IpDbDownloaded(ipdb::DbType::Geo, bytes) => {
+            state.geo_db_downloaded =
+                state.geo_db_downloaded.saturating_add(bytes);
+        }
+        AppEvent::SourcesTotal(proxy_type, amount) => {
+            state.sources_total.insert(proxy_type, amount);
+        }
+        AppEvent::SourceScraped(proxy_type) => {
+            state
+                .sources_scraped
+                .entry(proxy_type)
+                .and_modify(|c| *c = c.saturating_add(1))
+                .or_insert(1);
+        }
+        AppEvent::TotalProxies(proxy_type, amount) => {
+            state.proxies_total.insert(proxy_type, amount);
+        }
+        AppEvent::ProxyChecked(proxy_type) => {
+            state
+                .proxies_checked
+                .entry(proxy_type)
+                .and_modify(|c| *c = c.saturating_add(1))
+                .or_insert(1);
+        }
+        AppEvent::ProxyWorking(proxy_type) => {
+            state
+                .proxies_working
+                .entry(proxy_type)
+                .and_modify(|c| *c = c.saturating_add(1))
+                .or_insert(1);
+        }
+        AppEvent::ProxyIdentity {
+            protocol,
+            exit_ip_resolved,
+            anonymity,
+            proxy_protocol_supported,
+        } => {
+            if exit_ip_resolved {
+                state
+                    .exit_ips_resolved
+                    .entry(protocol)
+                    .and_modify(|c| *c = c.saturating_add(1))
+                    .or_insert(1);
+            }
+            let anonymity_counts = match anonymity {
+                Some(crate::proxy::Anonymity::Elite) => {
+                    Some(&mut state.elite_proxies)
                 }
-                AppEvent::Done => {
-                    if matches!(state.mode, AppMode::Running) {
-                        state.mode = AppMode::Done;
-                    }
+                Some(crate::proxy::Anonymity::Anonymous) => {
+                    Some(&mut state.anonymous_proxies)
                 }
-                AppEvent::Quit => {
-                    state.mode = AppMode::Quit;
+                Some(crate::proxy::Anonymity::Transparent) => {
+                    Some(&mut state.transparent_proxies)
                 }
+                None => None,
+            };
+            if let Some(counts) = anonymity_counts {
+                counts
+                    .entry(protocol)
+                    .and_modify(|c| *c = c.saturating_add(1))
+                    .or_insert(1);
             }
-            false
+            if proxy_protocol_supported == Some(true) {
+                state
+                    .proxy_protocol_supported
+                    .entry(protocol)
+                    .and_modify(|c| *c = c.saturating_add(1))
+                    .or_insert(1);
+            }
+        }
+        AppEvent::ProxyWorkingDetails {
+            protocol,
+            addr,
+            latency_secs,
+            country,
+            asn,
+            anonymity,
+        } => {
+            state.working_proxies.push(WorkingProxyDetail {
+                protocol,
+                addr,
+                latency_secs,
+                country,
+                asn,
+                anonymity,
+            });
+        }
+        AppEvent::Done => {
+            if matches!(state.mode, AppMode::Running | AppMode::Paused) {
+                state.mode = AppMode::Done;
+            }
+        }
+        AppEvent::Quit => {
+            state.mode = AppMode::Quit;
         }
     }
 }
@@ -0,0 +1,75 @@
+use std::{path::Path, sync::Arc, time::Duration};
+
+use color_eyre::eyre::WrapErr as _;
+use notify::Watcher as _;
+
+use crate::{config::Config, raw_config, utils::pretty_error};
+
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+async fn reload(config_path: &Path) -> crate::Result<Config> {
+    let raw_config = raw_config::read_config(config_path).await?;
+    Config::from_raw_config(raw_config).await
+}
+
+/// Watches `config_path` for changes, debounces bursts of filesystem events
+/// and, once they settle, reloads the config into `live_config`. A failed
+/// reload is logged and the previously loaded config keeps being used.
+pub async fn watch_and_reload(
+    config_path: Arc<Path>,
+    live_config: Arc<arc_swap::ArcSwap<Config>>,
+    reloaded: Arc<tokio::sync::Notify>,
+    token: tokio_util::sync::CancellationToken,
+) -> crate::Result<()> {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(
+        move |res: notify::Result<notify::Event>| {
+            if matches!(res, Ok(event) if event.kind.is_modify() || event.kind.is_create())
+            {
+                drop(tx.send(()));
+            }
+        },
+    )
+    .wrap_err("failed to create config file watcher")?;
+    watcher
+        .watch(&config_path, notify::RecursiveMode::NonRecursive)
+        .wrap_err_with(|| {
+            format!("failed to watch {}", config_path.display())
+        })?;
+
+    loop {
+        tokio::select! {
+            biased;
+            () = token.cancelled() => break,
+            maybe_event = rx.recv() => {
+                if maybe_event.is_none() {
+                    break;
+                }
+
+                tokio::time::sleep(DEBOUNCE).await;
+                while rx.try_recv().is_ok() {}
+
+                match reload(&config_path).await {
+                    Ok(config) => {
+                        live_config.store(Arc::new(config));
+                        tracing::info!(
+                            "Reloaded config from {}",
+                            config_path.display()
+                        );
+                        reloaded.notify_one();
+                    }
+                    Err(e) => {
+                        tracing::error!(
+                            "Failed to reload config, keeping the \
+                             previous one: {}",
+                            pretty_error(&e)
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    drop(watcher);
+    Ok(())
+}